@@ -0,0 +1,11 @@
+#version 330
+
+in vec2 v_corner;
+uniform vec4 color;
+out vec4 FragColor;
+
+void main() {
+    // round marker by discarding fragments outside unit circle
+    if (dot(v_corner, v_corner) > 1.0) { discard; }
+    FragColor = color;
+}
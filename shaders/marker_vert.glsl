@@ -0,0 +1,17 @@
+#version 330
+
+in vec2 corner; // unit quad corner, shared by all markers
+in vec3 position; // marker center, advanced once per instance
+uniform mat4 mvp;
+uniform vec3 range_min;
+uniform vec3 range_scale; // box units per data unit
+uniform vec2 viewport;
+uniform float size;
+out vec2 v_corner;
+
+void main() {
+    // extrude quad about projected center to marker diameter in px
+    gl_Position = mvp * vec4((position - range_min) * range_scale, 1.0);
+    gl_Position.xy += corner * size / viewport * gl_Position.w;
+    v_corner = corner;
+}
@@ -0,0 +1,10 @@
+#version 330
+
+in vec3 position;
+uniform vec2 viewport;
+
+void main() {
+    // convert window px with origin at top left to clip space
+    vec2 ndc = position.xy / viewport * 2.0 - 1.0;
+    gl_Position = vec4(ndc.x, -ndc.y, 0.0, 1.0);
+}
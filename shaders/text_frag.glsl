@@ -1,9 +1,12 @@
 #version 330
 
 uniform sampler2D fontTexture;
+uniform vec4 color;
 in vec2 v_texCoord;
 out vec4 FragColor;
 
 void main() {
-    FragColor = texture(fontTexture, v_texCoord);
+    // font map stores glyph coverage in all channels
+    float coverage = texture(fontTexture, v_texCoord).a;
+    FragColor = vec4(color.rgb, color.a * coverage);
 }
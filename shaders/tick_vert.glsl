@@ -0,0 +1,21 @@
+#version 330
+
+in vec3 position;
+in vec3 direction;
+in float offset;
+uniform mat4 mvp;
+uniform vec2 viewport;
+
+void main() {
+    vec4 pos = mvp * vec4(position, 1.0);
+    vec4 dir_pos = mvp * vec4(position + direction, 1.0);
+
+    // get tick direction in screen space px
+    vec2 p0 = pos.xy / pos.w * viewport;
+    vec2 p1 = dir_pos.xy / dir_pos.w * viewport;
+    vec2 dir = normalize(p1 - p0);
+
+    // offset vertex by px length along tick direction
+    gl_Position = pos;
+    gl_Position.xy += dir * offset * 2.0 / viewport * pos.w;
+}
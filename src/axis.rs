@@ -1,11 +1,13 @@
 extern crate alloc;
 extern crate gl;
 extern crate glam;
-use crate::gl_wrap::{Buffer, Program, Uniform, VertexArray};
+use crate::camera::Camera;
+use crate::gl_wrap::{Buffer, Program, VertexArray};
 use crate::plot::Bounds;
-use crate::scene::{DrawInds, DrawPass, Scene};
-use crate::text::{FontMap, TextParams, DEFAULT_FONT};
-use crate::vertices::{pos_vert, PosVert, TextVert};
+use crate::scene::{Scene, SceneBuilder};
+use crate::text::{FontMap, TextBatch, TextParams};
+use crate::vertices::{pos_vert, PosVert};
+use glam::Vec3;
 
 pub struct AxisLabels {
     pub x: String,
@@ -20,24 +22,33 @@ impl AxisLabels {
             x: "x axis".to_string(),
             y: "y axis".to_string(),
             z: "z axis".to_string(),
-            param: TextParams {
-                font: DEFAULT_FONT.to_string(),
-                size: 14.0,
-                kearning: 0.0,
-            },
+            param: TextParams::new(14.0),
         }
     }
 }
 
 pub struct Axis {
     pub color: [f32; 4],
+    pub style: BoxStyle,
+    pub panes: Option<[f32; 4]>,
     pub labels: AxisLabels,
 }
 
+// box edges drawn around plot bounds
+#[allow(dead_code)]
+pub enum BoxStyle {
+    Full,   // all twelve edges
+    Open,   // edges of back panes only
+    Floor,  // edges of floor pane only
+    Hidden, // no edges
+}
+
 impl Axis {
     pub fn new() -> Self {
         Self {
             color: [1.0, 1.0, 1.0, 1.0],
+            style: BoxStyle::Open,
+            panes: Some([1.0, 1.0, 1.0, 0.04]),
             labels: AxisLabels::new(),
         }
     }
@@ -46,147 +57,211 @@ impl Axis {
         &self,
         mvp: [f32; 16],
         bounds: &Bounds,
+        placement: &AxisPlacement,
         font: &FontMap,
     ) -> Result<Scene, AxisError> {
         // get vertex data for scene
-        let line_verts = Axis::get_verts(bounds);
-        let orient = Axis::get_label_orient(bounds);
-        let mut text_verts = Vec::<TextVert>::new();
-        text_verts.append(&mut font.get_verts(&self.labels.x, &self.labels.param, orient.x.pos)?);
-        let x_vlen = text_verts.len() as i32;
-        text_verts.append(&mut font.get_verts(&self.labels.y, &self.labels.param, orient.y.pos)?);
-        let y_vlen = text_verts.len() as i32;
-        text_verts.append(&mut font.get_verts(&self.labels.z, &self.labels.param, orient.z.pos)?);
-        let z_vlen = text_verts.len() as i32;
+        let line_verts = self.get_verts(bounds, placement);
+        let pane_verts = self.get_pane_verts(bounds, placement);
+        let orient = Axis::get_label_orient(bounds, placement);
+        let mut labels = TextBatch::new();
+        let placed = [
+            (&self.labels.x, orient.x),
+            (&self.labels.y, orient.y),
+            (&self.labels.z, orient.z),
+        ];
+        for (label, orient) in placed {
+            labels.group(font, &self.labels.param, Some(orient.align));
+            labels.add(label, orient.pos)?;
+        }
 
         // init gl resources for line drawing
+        let mut scene = SceneBuilder::new();
         const LINE_VERT: &str = "./shaders/solid_vert.glsl";
         const LINE_FRAG: &str = "./shaders/solid_frag.glsl";
-        let line_program = Program::new_from_files(LINE_VERT, LINE_FRAG)?;
+        let line_program = scene.add_program(Program::new_from_files(LINE_VERT, LINE_FRAG)?);
+        let line_pos_loc = scene
+            .program(line_program)
+            .get_attrib_location("position")?;
         let line_vao = VertexArray::new();
-        let line_buffer = Buffer::new_from(&line_verts, gl::STATIC_DRAW);
-        let line_pos_loc = line_program.get_attrib_location("position")?;
+        scene.add_buffer(Buffer::new_from(
+            &[line_verts.as_slice(), pane_verts.as_slice()].concat(),
+            gl::STATIC_DRAW,
+        ));
         line_vao.set_attribute::<PosVert>(line_pos_loc, 3, 0);
-        let u_mvp_line = Uniform::new(&line_program, "mvp", &mvp)?;
-        let u_color = Uniform::new(&line_program, "color", &self.color)?;
-
-        // init gl resources for text drawing
-        const TEXT_VERT: &str = "./shaders/text_align_vert.glsl";
-        const TEXT_FRAG: &str = "./shaders/text_frag.glsl";
-        let text_program = Program::new_from_files(TEXT_VERT, TEXT_FRAG)?;
-        let text_vao = VertexArray::new();
-        let text_buffer = Buffer::new_from(&text_verts, gl::STATIC_DRAW);
-        let text_pos_loc = text_program.get_attrib_location("position")?;
-        let text_off_loc = text_program.get_attrib_location("offset")?;
-        let text_tco_loc = text_program.get_attrib_location("a_texCoord")?;
-        text_vao.set_attribute::<TextVert>(text_pos_loc, 3, 0);
-        text_vao.set_attribute::<TextVert>(text_off_loc, 2, 3);
-        text_vao.set_attribute::<TextVert>(text_tco_loc, 2, 5);
-        let scale = font.scale * self.labels.param.size;
-        let u_scale = Uniform::new(&text_program, "scale", &[scale])?;
-        let u_align_x = Uniform::new(&text_program, "alignment", &orient.x.align)?;
-        let u_align_y = Uniform::new(&text_program, "alignment", &orient.y.align)?;
-        let u_align_z = Uniform::new(&text_program, "alignment", &orient.z.align)?;
-        let u_mvp_text = Uniform::new(&text_program, "mvp", &mvp)?;
-
-        let scene = Scene {
-            programs: vec![line_program, text_program],
-            vaos: vec![line_vao, text_vao],
-            buffers: vec![line_buffer, text_buffer],
-            textures: vec![font.texture],
-            uniforms: vec![
-                u_mvp_line, u_color, u_mvp_text, u_scale, u_align_x, u_align_y, u_align_z,
-            ],
-            passes: vec![
-                // axis lines
-                DrawPass {
-                    draw_type: gl::LINES,
-                    start: 0,
-                    count: line_verts.len() as i32,
-                    inds: DrawInds {
-                        program: 0,
-                        vao: 0,
-                        texture: None,
-                        uniform: vec![0, 1],
-                    },
-                },
-                // x label
-                DrawPass {
-                    draw_type: gl::TRIANGLES,
-                    start: 0,
-                    count: x_vlen,
-                    inds: DrawInds {
-                        program: 1,
-                        vao: 1,
-                        texture: Some(0),
-                        uniform: vec![2, 3, 4],
-                    },
-                },
-                // y label
-                DrawPass {
-                    draw_type: gl::TRIANGLES,
-                    start: x_vlen,
-                    count: y_vlen - x_vlen,
-                    inds: DrawInds {
-                        program: 1,
-                        vao: 1,
-                        texture: Some(0),
-                        uniform: vec![2, 3, 5],
-                    },
-                },
-                // z label
-                DrawPass {
-                    draw_type: gl::TRIANGLES,
-                    start: y_vlen,
-                    count: z_vlen - y_vlen,
-                    inds: DrawInds {
-                        program: 1,
-                        vao: 1,
-                        texture: Some(0),
-                        uniform: vec![2, 3, 6],
-                    },
-                },
-            ],
-        };
-        Ok(scene)
+        let line_vao = scene.add_vao(line_vao, line_program);
+        let u_mvp_line = scene.add_uniform(line_program, "mvp", &mvp)?;
+        let u_color = scene.add_uniform(line_program, "color", &self.color)?;
+        let pane_color = self.panes.unwrap_or([0.0; 4]);
+        let u_pane_color = scene.add_uniform(line_program, "color", &pane_color)?;
+
+        // back panes
+        scene.add_pass(
+            gl::TRIANGLES,
+            (line_verts.len(), pane_verts.len()),
+            line_program,
+            line_vao,
+            None,
+            &[u_mvp_line, u_pane_color],
+        );
+        // axis lines
+        scene.add_pass(
+            gl::LINES,
+            (0, line_verts.len()),
+            line_program,
+            line_vao,
+            None,
+            &[u_mvp_line, u_color],
+        );
+        // x, y, z label backgrounds, borders and text turned along their edges
+        labels.add_to_scene(&mut scene, mvp)?;
+        Ok(scene.build()?)
+    }
+
+    fn get_verts(&self, b: &Bounds, placement: &AxisPlacement) -> Vec<PosVert> {
+        let max = [b.x, b.y, b.z];
+        let back = placement.back.to_array();
+        let mut verts = Vec::<PosVert>::new();
+        // each edge runs along axis a, fixed at min or max of other axes u and v
+        for a in 0..3 {
+            let (u, v) = ((a + 1) % 3, (a + 2) % 3);
+            for (pu, pv) in [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)] {
+                let mut start = [0.0; 3];
+                start[u] = pu * max[u];
+                start[v] = pv * max[v];
+                let on_back = |k: usize| start[k] == back[k];
+                let visible = match self.style {
+                    BoxStyle::Full => true,
+                    BoxStyle::Open => on_back(u) || on_back(v),
+                    BoxStyle::Floor => (u == 1 && on_back(u)) || (v == 1 && on_back(v)),
+                    BoxStyle::Hidden => false,
+                };
+                if visible {
+                    let mut end = start;
+                    end[a] = max[a];
+                    verts.append(&mut pos_vert![start, end]);
+                }
+            }
+        }
+        verts
     }
 
-    fn get_verts(b: &Bounds) -> Vec<PosVert> {
-        pos_vert![
-            [b.x, b.y, 0.0],
-            [b.x, 0.0, 0.0],
-            [b.x, 0.0, 0.0],
-            [b.x, 0.0, b.z],
-            [b.x, 0.0, b.z],
-            [0.0, 0.0, b.z],
-            [0.0, 0.0, b.z],
-            [0.0, b.y, b.z],
-            [0.0, b.y, b.z],
-            [0.0, b.y, 0.0],
-            [0.0, b.y, 0.0],
-            [b.x, b.y, 0.0]
-        ]
+    // quads covering box faces furthest from camera
+    fn get_pane_verts(&self, b: &Bounds, placement: &AxisPlacement) -> Vec<PosVert> {
+        if self.panes.is_none() {
+            return vec![];
+        }
+        let max = [b.x, b.y, b.z];
+        let back = placement.back.to_array();
+        let mut verts = Vec::<PosVert>::new();
+        for k in 0..3 {
+            let (u, v) = ((k + 1) % 3, (k + 2) % 3);
+            let corner = |pu: f32, pv: f32| {
+                let mut pos = [0.0; 3];
+                pos[k] = back[k];
+                pos[u] = pu * max[u];
+                pos[v] = pv * max[v];
+                pos
+            };
+            verts.append(&mut pos_vert![
+                corner(1.0, 1.0),
+                corner(0.0, 1.0),
+                corner(0.0, 0.0),
+                corner(0.0, 0.0),
+                corner(1.0, 0.0),
+                corner(1.0, 1.0)
+            ]);
+        }
+        verts
     }
 
-    fn get_label_orient(b: &Bounds) -> LabelOrientations {
+    fn get_label_orient(b: &Bounds, placement: &AxisPlacement) -> LabelOrientations {
         const M: f32 = 0.15; // label margin
         LabelOrientations {
             x: LabelOrientation {
-                pos: [b.x * 0.5, 0.0, b.z + M],
+                pos: placement.x.point(b.x * 0.5, M),
                 align: [1.0, 0.0, 0.0],
             },
             y: LabelOrientation {
-                pos: [b.x + M, b.y * 0.5, 0.0],
+                pos: placement.y.point(b.y * 0.5, M),
                 align: [0.0, -1.0, 0.0],
             },
             z: LabelOrientation {
-                pos: [b.x + M, 0.0, b.z * 0.5],
+                pos: placement.z.point(b.z * 0.5, M),
                 align: [0.0, 0.0, 1.0],
             },
         }
     }
 }
 
+// box edges that ticks and labels of each axis are drawn on for current view
+#[derive(PartialEq)]
+pub struct AxisPlacement {
+    pub x: EdgePlacement,
+    pub y: EdgePlacement,
+    pub z: EdgePlacement,
+    pub back: Vec3, // per axis coordinate of box face furthest from camera
+}
+
+#[derive(PartialEq)]
+pub struct EdgePlacement {
+    pub origin: Vec3, // edge end at axis value 0
+    pub dir: Vec3,    // unit direction of axis
+    pub out: Vec3,    // unit direction from edge away from box
+}
+
+impl EdgePlacement {
+    // point on edge at axis value t, moved out from box by margin m
+    pub fn point(&self, t: f32, m: f32) -> [f32; 3] {
+        (self.origin + self.dir * t + self.out * m).to_array()
+    }
+}
+
+impl AxisPlacement {
+    // choose visible box edges for each axis from camera position
+    pub fn from_view(b: &Bounds, camera: &Camera) -> Self {
+        let center = Vec3::new(b.x, b.y, b.z) * 0.5;
+        let eye = camera.eye - center;
+        let side = |e: f32, max: f32| if e >= 0.0 { (max, 1.0) } else { (0.0, -1.0) };
+        let (near_x, out_x) = side(eye.x, b.x);
+        let (near_z, out_z) = side(eye.z, b.z);
+        let (far_x, far_z) = (b.x - near_x, b.z - near_z);
+
+        // horizontal axes on near edges of floor, or ceiling if viewed from below
+        let floor = if eye.y >= 0.0 { 0.0 } else { b.y };
+        let x = EdgePlacement {
+            origin: Vec3::new(0.0, floor, near_z),
+            dir: Vec3::X,
+            out: Vec3::new(0.0, 0.0, out_z),
+        };
+        let z = EdgePlacement {
+            origin: Vec3::new(near_x, floor, 0.0),
+            dir: Vec3::Z,
+            out: Vec3::new(out_x, 0.0, 0.0),
+        };
+
+        // vertical axis on silhouette edge furthest right on screen
+        let right = camera.right();
+        let near_x_edge = Vec3::new(near_x, 0.0, far_z);
+        let near_z_edge = Vec3::new(far_x, 0.0, near_z);
+        let y = if (near_x_edge - center).dot(right) >= (near_z_edge - center).dot(right) {
+            EdgePlacement {
+                origin: near_x_edge,
+                dir: Vec3::Y,
+                out: Vec3::new(out_x, 0.0, 0.0),
+            }
+        } else {
+            EdgePlacement {
+                origin: near_z_edge,
+                dir: Vec3::Y,
+                out: Vec3::new(0.0, 0.0, out_z),
+            }
+        };
+        let back = Vec3::new(far_x, floor, far_z);
+        Self { x, y, z, back }
+    }
+}
+
 struct LabelOrientations {
     pub x: LabelOrientation,
     pub y: LabelOrientation,
@@ -200,7 +275,8 @@ struct LabelOrientation {
 
 extern crate thiserror;
 use crate::gl_wrap::{ProgramError, ShaderError, UniformError};
-use crate::text::FontMapError;
+use crate::scene::SceneError;
+use crate::text::{FontMapError, TextSceneError};
 use std::ffi::NulError;
 use thiserror::Error;
 #[derive(Error, Debug)]
@@ -215,4 +291,8 @@ pub enum AxisError {
     Nul(#[from] NulError),
     #[error("{0}")]
     Font(#[from] FontMapError),
+    #[error("{0}")]
+    Scene(#[from] SceneError),
+    #[error("{0}")]
+    Text(#[from] TextSceneError),
 }
@@ -0,0 +1,301 @@
+extern crate glam;
+extern crate serde;
+extern crate serde_json;
+use glam::{Mat4, Quat, Vec3};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+pub struct Camera {
+    pub eye: Vec3,
+    pub target: Vec3,
+    pub up: Vec3,
+    pub fov: f32,
+    pub aspect: f32,
+    pub radius: f32, // radius of sphere containing scene, used for clip planes
+    pub projection: Projection,
+    home_eye: Vec3,
+    home_target: Vec3,
+    explicit: bool, // eye and target restored from state, kept when refit
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Projection {
+    Perspective,
+    Orthographic,
+}
+
+// serializable camera parameters for reproducing a view
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CameraState {
+    pub eye: [f32; 3],
+    pub target: [f32; 3],
+    pub up: [f32; 3],
+    pub fov: f32,
+    pub projection: Projection,
+}
+
+impl CameraState {
+    pub fn to_json(&self) -> Result<String, CameraError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, CameraError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    #[allow(dead_code)]
+    pub fn save(&self, file: &str) -> Result<(), CameraError> {
+        fs::write(file, self.to_json()?)?;
+        Ok(())
+    }
+
+    #[allow(dead_code)]
+    pub fn load(file: &str) -> Result<Self, CameraError> {
+        Self::from_json(&fs::read_to_string(file)?)
+    }
+
+    // state must give view matrix and projection, so fov lies in (0, pi),
+    // eye differs from target and up is not along view direction
+    pub fn validate(&self) -> Result<(), CameraError> {
+        let (eye, target, up) = (
+            Vec3::from(self.eye),
+            Vec3::from(self.target),
+            Vec3::from(self.up),
+        );
+        let finite = eye.is_finite() && target.is_finite() && up.is_finite();
+        let dir = (target - eye).normalize_or_zero();
+        let side = dir.cross(up.normalize_or_zero()).length();
+        let fov_valid = self.fov > 0.0 && self.fov < std::f32::consts::PI;
+        match finite && fov_valid && side > MIN_UP_ANGLE.sin() {
+            true => Ok(()),
+            false => Err(CameraError::State),
+        }
+    }
+}
+
+// preset camera views of y-up scene
+#[allow(dead_code)]
+#[derive(Copy, Clone)]
+pub enum View {
+    Default,
+    Top,   // looking down along -y onto xz plane
+    Front, // looking along -z onto xy plane
+    Side,  // looking along -x onto yz plane
+    Isometric,
+}
+
+impl Camera {
+    pub fn new(aspect: f32) -> Self {
+        Self {
+            eye: DEFAULT_EYE,
+            target: Vec3::ZERO,
+            up: Vec3::Y,
+            fov: DEFAULT_FOV,
+            aspect,
+            radius: DEFAULT_EYE.length(),
+            projection: Projection::Perspective,
+            home_eye: DEFAULT_EYE,
+            home_target: Vec3::ZERO,
+            explicit: false,
+        }
+    }
+
+    // aim at center of box spanning origin to extents from current direction,
+    // at distance where box and its labels fill the view,
+    // only updating clip planes if eye and target were set from state
+    pub fn fit(&mut self, extents: Vec3) {
+        self.radius = extents.length() * 0.5 * FIT_MARGIN;
+        let half_fov_y = self.fov * 0.5;
+        let half_fov_x = (half_fov_y.tan() * self.aspect).atan();
+        let distance = self.radius / half_fov_y.min(half_fov_x).sin();
+        self.home_target = extents * 0.5;
+        self.home_eye = self.home_target + DEFAULT_EYE.normalize() * distance;
+        if !self.explicit {
+            let dir = (self.eye - self.target).normalize();
+            self.target = self.home_target;
+            self.eye = self.target + dir * distance;
+        }
+    }
+
+    pub fn state(&self) -> CameraState {
+        CameraState {
+            eye: self.eye.to_array(),
+            target: self.target.to_array(),
+            up: self.up.to_array(),
+            fov: self.fov,
+            projection: self.projection,
+        }
+    }
+
+    // restore view from state, leaving camera unchanged if state is invalid
+    pub fn set_state(&mut self, state: &CameraState) -> Result<(), CameraError> {
+        state.validate()?;
+        self.eye = Vec3::from(state.eye);
+        self.target = Vec3::from(state.target);
+        self.up = Vec3::from(state.up);
+        self.fov = state.fov;
+        self.projection = state.projection;
+        self.explicit = true;
+        Ok(())
+    }
+
+    pub fn mvp(&self) -> [f32; 16] {
+        // clip planes tightly around scene sphere for depth precision
+        let distance = (self.eye - self.target).length();
+        let near = (distance - self.radius).max(distance * MIN_NEAR);
+        let far = distance + self.radius;
+        let proj_matrix = match self.projection {
+            Projection::Perspective => Mat4::perspective_rh_gl(self.fov, self.aspect, near, far),
+            Projection::Orthographic => {
+                // match size of perspective view frustum at target distance
+                let h = distance * (self.fov * 0.5).tan();
+                let w = h * self.aspect;
+                Mat4::orthographic_rh_gl(-w, w, -h, h, near, far)
+            }
+        };
+        let view_matrix = Mat4::look_at_rh(self.eye, self.target, self.up);
+        proj_matrix.mul_mat4(&view_matrix).to_cols_array()
+    }
+
+    pub fn toggle_projection(&mut self) {
+        self.projection = match self.projection {
+            Projection::Perspective => Projection::Orthographic,
+            Projection::Orthographic => Projection::Perspective,
+        };
+    }
+
+    // match view direction, up and projection of other camera,
+    // keeping own target and distance for scene of different size
+    pub fn link_to(&mut self, state: &CameraState) {
+        let dir = (Vec3::from(state.eye) - Vec3::from(state.target)).normalize();
+        let distance = (self.eye - self.target).length();
+        self.eye = self.target + dir * distance;
+        self.up = Vec3::from(state.up);
+        self.projection = state.projection;
+    }
+
+    // move eye to preset view, keeping current distance from target,
+    // after which view follows refits again
+    pub fn set_view(&mut self, view: View) {
+        self.explicit = false;
+        let distance = (self.eye - self.target).length();
+        let dir = match view {
+            View::Default => {
+                self.eye = self.home_eye;
+                self.target = self.home_target;
+                return;
+            }
+            // offset from pole to keep view matrix well defined
+            View::Top => Vec3::new(0.0, MIN_POLAR.cos(), MIN_POLAR.sin()),
+            View::Front => Vec3::Z,
+            View::Side => Vec3::X,
+            View::Isometric => Vec3::ONE.normalize(),
+        };
+        self.eye = self.target + dir * distance;
+    }
+
+    // unit vector from eye towards target
+    pub fn view_dir(&self) -> Vec3 {
+        (self.target - self.eye).normalize()
+    }
+
+    // unit vector pointing right in screen space
+    pub fn right(&self) -> Vec3 {
+        self.view_dir().cross(self.up).normalize()
+    }
+
+    // rotate eye about target, yaw around up vector and pitch around right vector
+    pub fn orbit(&mut self, yaw: f32, pitch: f32) {
+        let offset = self.eye - self.target;
+        let yawed = Quat::from_axis_angle(self.up, -yaw) * offset;
+
+        // clamp pitch to avoid flipping over poles
+        let angle = yawed.angle_between(self.up);
+        let pitch = pitch.clamp(MIN_POLAR - angle, MAX_POLAR - angle);
+        let right = (-yawed).cross(self.up).normalize();
+        let pitched = Quat::from_axis_angle(right, pitch) * yawed;
+
+        self.eye = self.target + pitched;
+    }
+}
+
+pub static DEFAULT_EYE: Vec3 = Vec3::new(2.0, 2.0, 2.0);
+static DEFAULT_FOV: f32 = 50.0 * std::f32::consts::PI / 180.0;
+static FIT_MARGIN: f32 = 1.25; // scale of bounding radius leaving room for labels
+static MIN_NEAR: f32 = 0.01; // near plane limit as fraction of eye distance
+static MIN_POLAR: f32 = 0.01;
+static MAX_POLAR: f32 = std::f32::consts::PI - 0.01;
+static MIN_UP_ANGLE: f32 = 0.001; // radians between up and view direction of valid state
+
+extern crate thiserror;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CameraError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Camera state needs fov in (0, pi), eye apart from target and up off view direction")]
+    State,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> CameraState {
+        CameraState {
+            eye: [2.0, 1.5, -3.0],
+            target: [0.5, 0.5, 0.5],
+            up: [0.0, 1.0, 0.0],
+            fov: 0.8,
+            projection: Projection::Orthographic,
+        }
+    }
+
+    #[test]
+    fn state_round_trips_through_json() {
+        let mut camera = Camera::new(1.5);
+        camera.set_state(&state()).unwrap();
+        let json = camera.state().to_json().unwrap();
+        let restored = CameraState::from_json(&json).unwrap();
+        let expected = state();
+        assert_eq!(restored.eye, expected.eye);
+        assert_eq!(restored.target, expected.target);
+        assert_eq!(restored.up, expected.up);
+        assert_eq!(restored.fov, expected.fov);
+        assert!(restored.projection == expected.projection);
+        let mut other = Camera::new(1.0);
+        other.set_state(&restored).unwrap();
+        assert_eq!(
+            other.mvp(),
+            Camera {
+                aspect: 1.0,
+                ..camera
+            }
+            .mvp()
+        );
+    }
+
+    #[test]
+    fn invalid_states_are_rejected() {
+        let invalid: [fn(&mut CameraState); 6] = [
+            |s| s.fov = 0.0,
+            |s| s.fov = std::f32::consts::PI,
+            |s| s.fov = f32::NAN,
+            |s| s.eye = s.target,
+            |s| s.up = [0.0; 3],
+            // up along view direction
+            |s| s.up = [-1.5, -1.0, 3.5],
+        ];
+        for change in invalid {
+            let mut camera = Camera::new(1.0);
+            let before = camera.mvp();
+            let mut state = state();
+            change(&mut state);
+            assert!(camera.set_state(&state).is_err());
+            assert_eq!(camera.mvp(), before);
+        }
+    }
+}
@@ -0,0 +1,333 @@
+extern crate gl;
+extern crate glutin;
+use crate::gl_wrap::{Renderer, Window, WindowSet};
+use crate::plot::{Plot, PlotError, PlotView};
+use crate::plot2d::{Plot2d, Plot2dView};
+use glutin::dpi::PhysicalPosition;
+use glutin::event::{ElementState, WindowEvent};
+
+// window split into grid of independent plots
+pub struct Figure {
+    size: [f32; 2],
+    cols: usize,
+    cells: Vec<Cell>,
+    window: Window,
+    linked: bool,
+}
+
+// contents of single figure cell, few per figure so left unboxed
+#[allow(clippy::large_enum_variant)]
+enum Cell {
+    Plot(Plot),
+    Plot2d(Plot2d),
+}
+
+impl Figure {
+    pub fn new(
+        title: &str,
+        width: f64,
+        height: f64,
+        rows: usize,
+        cols: usize,
+    ) -> Result<Self, PlotError> {
+        let (rows, cols) = (rows.max(1), cols.max(1));
+        let window = Window::new(title, width, height)?;
+        let cell = [width / cols as f64, height / rows as f64];
+        let cells = (0..rows * cols)
+            .map(|_| Plot::new_cell(cell, [width, height]).map(Cell::Plot))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            window,
+            size: [width as f32, height as f32],
+            cols,
+            cells,
+            linked: false,
+        })
+    }
+
+    // figure with single cell filled by plot and its window
+    pub fn from_plot(mut plot: Plot) -> Self {
+        // plots outside figures always own a window
+        let window = plot.take_window().unwrap();
+        Self {
+            window,
+            size: plot.size(),
+            cols: 1,
+            cells: vec![Cell::Plot(plot)],
+            linked: false,
+        }
+    }
+
+    fn rows(&self) -> usize {
+        self.cells.len() / self.cols
+    }
+
+    // index of cell at row and column, none if outside grid
+    fn cell_index(&self, row: usize, col: usize) -> Option<usize> {
+        match row < self.rows() && col < self.cols {
+            true => Some(row * self.cols + col),
+            false => None,
+        }
+    }
+
+    // 3d plot in cell at row and column counted from top left,
+    // none if cell is outside grid or holds 2d plot
+    pub fn plot(&mut self, row: usize, col: usize) -> Option<&mut Plot> {
+        let i = self.cell_index(row, col)?;
+        match &mut self.cells[i] {
+            Cell::Plot(plot) => Some(plot),
+            Cell::Plot2d(_) => None,
+        }
+    }
+
+    // 2d plot in cell at row and column, replacing any 3d plot in cell
+    pub fn plot_2d(&mut self, row: usize, col: usize) -> Result<&mut Plot2d, PlotError> {
+        let rows = self.rows();
+        let i = self
+            .cell_index(row, col)
+            .ok_or(PlotError::Cell([row, col], [rows, self.cols]))?;
+        let cell_size = [
+            self.size[0] as f64 / self.cols as f64,
+            self.size[1] as f64 / rows as f64,
+        ];
+        let window_size = [self.size[0] as f64, self.size[1] as f64];
+        if let Cell::Plot(_) = self.cells[i] {
+            // font mapper gl resources created in figure window context
+            self.window.make_current()?;
+            self.cells[i] = Cell::Plot2d(Plot2d::new_cell(cell_size, window_size)?);
+        }
+        match &mut self.cells[i] {
+            Cell::Plot2d(plot) => Ok(plot),
+            Cell::Plot(_) => unreachable!(),
+        }
+    }
+
+    // rotate all 3d plots together when any one is rotated
+    pub fn link_cameras(&mut self, linked: bool) {
+        self.linked = linked;
+    }
+
+    // show figure, blocking until its window is closed
+    #[allow(dead_code)]
+    pub fn display(self) -> Result<(), PlotError> {
+        let mut windows = PlotWindows::new(vec![])?;
+        windows.add_figure(self)?;
+        windows.run()
+    }
+
+    // build gl resources of all plots in figure window context
+    fn into_view(self) -> Result<(Window, FigureView), PlotError> {
+        self.window.make_current()?;
+        let cells = self
+            .cells
+            .into_iter()
+            .map(|cell| match cell {
+                Cell::Plot(plot) => plot.into_view().map(CellView::Plot),
+                Cell::Plot2d(plot) => plot.into_view().map(CellView::Plot2d),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let view = FigureView {
+            size: self.size,
+            scale_factor: self.window.scale_factor(),
+            rows: cells.len() / self.cols,
+            cols: self.cols,
+            cells,
+            linked: self.linked,
+            active: 0,
+            pressed: false,
+        };
+        Ok((self.window, view))
+    }
+}
+
+// figure state owned by window event loop, routing input to plot under cursor
+pub(crate) struct FigureView {
+    size: [f32; 2],    // logical px, like cursor positions passed to cells
+    scale_factor: f64, // physical px per logical px
+    rows: usize,
+    cols: usize,
+    cells: Vec<CellView>,
+    linked: bool,
+    active: usize, // cell receiving input
+    pressed: bool, // mouse held, keeping input on active cell while dragging
+}
+
+// cell state owned by figure view, 2d plots are static and ignore input
+#[allow(clippy::large_enum_variant)]
+enum CellView {
+    Plot(PlotView),
+    Plot2d(Plot2dView),
+}
+
+impl CellView {
+    fn handle_event(&mut self, event: &WindowEvent) -> bool {
+        match self {
+            CellView::Plot(view) => view.handle_event(event),
+            CellView::Plot2d(_) => false,
+        }
+    }
+
+    fn update(&mut self) -> bool {
+        match self {
+            CellView::Plot(view) => view.update(),
+            CellView::Plot2d(_) => false,
+        }
+    }
+
+    fn draw(&mut self) -> Result<(), PlotError> {
+        match self {
+            CellView::Plot(view) => view.draw(),
+            CellView::Plot2d(view) => view.draw(),
+        }
+    }
+}
+
+impl FigureView {
+    fn cell_size(&self) -> [f32; 2] {
+        [
+            self.size[0] / self.cols as f32,
+            self.size[1] / self.rows as f32,
+        ]
+    }
+
+    // top left corner of cell in window px
+    fn origin(&self, cell: usize) -> [f32; 2] {
+        let [w, h] = self.cell_size();
+        [(cell % self.cols) as f32 * w, (cell / self.cols) as f32 * h]
+    }
+
+    fn cell_at(&self, position: [f32; 2]) -> usize {
+        let [w, h] = self.cell_size();
+        let col = ((position[0] / w).max(0.0) as usize).min(self.cols - 1);
+        let row = ((position[1] / h).max(0.0) as usize).min(self.rows - 1);
+        row * self.cols + col
+    }
+
+    // copy view direction of active plot to all other 3d plots
+    fn sync_cameras(&mut self) {
+        let state = match &self.cells[self.active] {
+            CellView::Plot(view) => view.camera().state(),
+            CellView::Plot2d(_) => return,
+        };
+        for (i, cell) in self.cells.iter_mut().enumerate() {
+            if let (true, CellView::Plot(view)) = (i != self.active, cell) {
+                view.link_camera(&state);
+            }
+        }
+    }
+}
+
+impl Renderer for FigureView {
+    type Error = PlotError;
+
+    fn handle_event(&mut self, event: &WindowEvent) -> bool {
+        let changed = match event {
+            // convert cursor from physical px to logical position within cell under cursor
+            #[allow(deprecated)]
+            WindowEvent::CursorMoved {
+                device_id,
+                position,
+                modifiers,
+            } => {
+                let position = position.to_logical::<f64>(self.scale_factor);
+                if !self.pressed {
+                    self.active = self.cell_at([position.x as f32, position.y as f32]);
+                }
+                let origin = self.origin(self.active);
+                let event = WindowEvent::CursorMoved {
+                    device_id: *device_id,
+                    position: PhysicalPosition::new(
+                        position.x - origin[0] as f64,
+                        position.y - origin[1] as f64,
+                    ),
+                    modifiers: *modifiers,
+                };
+                self.cells[self.active].handle_event(&event)
+            }
+            WindowEvent::MouseInput { state, .. } => {
+                self.pressed = *state == ElementState::Pressed;
+                self.cells[self.active].handle_event(event)
+            }
+            // e.g. window moved to display with other dpi
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                self.scale_factor = *scale_factor;
+                true
+            }
+            _ => self.cells[self.active].handle_event(event),
+        };
+        if changed && self.linked {
+            self.sync_cameras();
+        }
+        changed
+    }
+
+    fn update(&mut self) -> bool {
+        let mut changed = false;
+        for cell in &mut self.cells {
+            changed |= cell.update();
+        }
+        changed
+    }
+
+    fn draw(&mut self) -> Result<(), PlotError> {
+        // gl viewport measured in physical px from bottom left of window
+        let scale = self.scale_factor as f32;
+        let [w, h] = self.cell_size().map(|side| (side * scale) as i32);
+        unsafe {
+            gl::Enable(gl::SCISSOR_TEST);
+        }
+        for i in 0..self.cells.len() {
+            let [x, y] = self.origin(i);
+            let (x, y) = ((x * scale) as i32, ((self.size[1] - y) * scale) as i32 - h);
+            unsafe {
+                gl::Viewport(x, y, w, h);
+                gl::Scissor(x, y, w, h);
+            }
+            self.cells[i].draw()?;
+        }
+        let [w, h] = self.size.map(|side| (side * scale) as i32);
+        unsafe {
+            gl::Disable(gl::SCISSOR_TEST);
+            gl::Viewport(0, 0, w, h);
+        }
+        Ok(())
+    }
+}
+
+// plot windows open at once, sharing one event loop
+pub struct PlotWindows {
+    windows: WindowSet<FigureView>,
+}
+
+impl PlotWindows {
+    pub fn new(plots: Vec<Plot>) -> Result<Self, PlotError> {
+        let mut windows = Self {
+            windows: WindowSet::new(),
+        };
+        for plot in plots {
+            windows.add(plot)?;
+        }
+        Ok(windows)
+    }
+
+    pub fn add(&mut self, plot: Plot) -> Result<(), PlotError> {
+        self.add_figure(Figure::from_plot(plot))
+    }
+
+    pub fn add_figure(&mut self, figure: Figure) -> Result<(), PlotError> {
+        let (window, view) = figure.into_view()?;
+        self.windows.push(window, view);
+        Ok(())
+    }
+
+    // block until all windows are closed or one fails to draw
+    pub fn run(mut self) -> Result<(), PlotError> {
+        self.windows.run()
+    }
+
+    // handle pending input and redraws, returning false once all windows are closed
+    #[allow(dead_code)]
+    pub fn poll(&mut self) -> Result<bool, PlotError> {
+        self.windows.poll()
+    }
+}
@@ -1,74 +1,286 @@
 extern crate gl;
 extern crate glutin;
-use crate::scene::Scene;
-use gl::types::{GLenum, GLint, GLsizeiptr, GLuint};
+use gl::types::{GLchar, GLenum, GLint, GLintptr, GLsizei, GLsizeiptr, GLuint};
 use glutin::dpi::LogicalSize;
 use glutin::event::{Event, WindowEvent};
-use glutin::event_loop::{ControlFlow, EventLoop};
-use glutin::window::WindowBuilder;
-use glutin::{Api, ContextBuilder, ContextWrapper, CreationError, GlRequest, PossiblyCurrent};
+use glutin::event_loop::{ControlFlow, EventLoop, EventLoopProxy};
+use glutin::platform::run_return::EventLoopExtRunReturn;
+use glutin::window::{WindowBuilder, WindowId};
+use glutin::{
+    Api, ContextBuilder, ContextError, ContextWrapper, CreationError, GlRequest, PossiblyCurrent,
+};
+use std::cell::RefCell;
 use std::ffi::{CString, NulError};
+use std::rc::{Rc, Weak};
 use std::string::FromUtf8Error;
 use std::{fs, ptr};
 
+thread_local! {
+    // single event loop shared by all windows opened on this thread
+    static EVENT_LOOP: RefCell<Option<EventLoop<()>>> = const { RefCell::new(None) };
+    // proxy of shared event loop, stored when loop is created so it can be cloned while running
+    static PROXY: RefCell<Option<EventLoopProxy<()>>> = const { RefCell::new(None) };
+    // context last made current on this thread, weak so closed windows are not kept open
+    static CURRENT: RefCell<Weak<RefCell<Option<GlContext>>>> = const { RefCell::new(Weak::new()) };
+}
+
+// run f with shared event loop, created on first use
+// loop is borrowed while running, so windows cannot be opened or run from renderer callbacks
+fn with_event_loop<T>(f: impl FnOnce(&mut EventLoop<()>) -> T) -> Result<T, WindowError> {
+    EVENT_LOOP.with(|event_loop| {
+        let mut event_loop = event_loop
+            .try_borrow_mut()
+            .map_err(|_| WindowError::Running)?;
+        let event_loop = event_loop.get_or_insert_with(|| {
+            let event_loop = EventLoop::new();
+            PROXY.with(|proxy| *proxy.borrow_mut() = Some(event_loop.create_proxy()));
+            event_loop
+        });
+        Ok(f(event_loop))
+    })
+}
+
+type GlContext = ContextWrapper<PossiblyCurrent, glutin::window::Window>;
+
+// shared handle to window gl context, kept by every gl object created while it is current
+// so objects are freed in their own context, which is destroyed with its last holder
+#[derive(Clone)]
+pub struct Context(Rc<RefCell<Option<GlContext>>>);
+
+impl Context {
+    fn new(ctx: GlContext) -> Self {
+        let context = Self(Rc::new(RefCell::new(Some(ctx))));
+        context.set_current();
+        context
+    }
+
+    // context new gl objects are created in, none before any window is opened
+    fn current() -> Option<Self> {
+        CURRENT.with(|current| current.borrow().upgrade().map(Self))
+    }
+
+    fn is_current(&self) -> bool {
+        CURRENT.with(|current| current.borrow().as_ptr() == Rc::as_ptr(&self.0))
+    }
+
+    fn set_current(&self) {
+        CURRENT.with(|current| *current.borrow_mut() = Rc::downgrade(&self.0));
+    }
+
+    // make context target of following gl calls
+    pub fn make_current(&self) -> Result<(), ContextError> {
+        if self.is_current() {
+            return Ok(());
+        }
+        let mut slot = self.0.borrow_mut();
+        let ctx = slot.take().unwrap();
+        match unsafe { ctx.make_current() } {
+            Ok(ctx) => {
+                *slot = Some(ctx);
+                self.set_current();
+                Ok(())
+            }
+            Err((ctx, e)) => {
+                *slot = Some(ctx);
+                Err(e)
+            }
+        }
+    }
+}
+
+// free gl object in context it was created in, leaking it if that context cannot be
+// made current rather than deleting object sharing its id in another context
+fn free_in(ctx: &Option<Context>, free: impl FnOnce()) {
+    let current = match ctx {
+        Some(ctx) => ctx.make_current().is_ok(),
+        None => true,
+    };
+    if current {
+        free();
+    }
+}
+
+// gl objects created in window context keep it alive, so window stays open until
+// both window and all of its objects are dropped
 pub struct Window {
-    ctx: ContextWrapper<PossiblyCurrent, glutin::window::Window>,
-    event_loop: EventLoop<()>,
+    ctx: Context,
 }
 
 impl Window {
-    // initialize window with OpenGl 3.3 context
-    pub fn new(title: &str, width: f64, height: f64) -> Result<Self, CreationError> {
+    // initialize window with OpenGl 3.3 context, leaving its context current
+    pub fn new(title: &str, width: f64, height: f64) -> Result<Self, WindowError> {
         let window = WindowBuilder::new()
             .with_inner_size(LogicalSize::new(width, height))
             .with_title(title);
-        let event_loop = EventLoop::new();
-        let ctx = ContextBuilder::new()
-            .with_gl(GlRequest::Specific(Api::OpenGl, (3, 3)))
-            .with_multisampling(4)
-            .build_windowed(window, &event_loop)?;
+        let ctx = with_event_loop(|event_loop| {
+            ContextBuilder::new()
+                .with_gl(GlRequest::Specific(Api::OpenGl, (3, 3)))
+                .with_multisampling(4)
+                .build_windowed(window, event_loop)
+        })??;
         unsafe {
-            let ctx = ctx.make_current().unwrap();
+            let ctx = ctx.make_current().map_err(|(_, e)| e)?;
             gl::load_with(|ptr| ctx.get_proc_address(ptr) as *const _);
             gl::Enable(gl::BLEND);
             gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
-            Ok(Self { ctx, event_loop })
+            Ok(Self {
+                ctx: Context::new(ctx),
+            })
         }
     }
 
-    // begin draw loop with generic user defined scenes
-    pub fn run(self, scenes: Vec<Scene>) {
-        self.ctx.swap_buffers().unwrap();
-        self.event_loop.run(move |event, _, control_flow| {
-            *control_flow = ControlFlow::Wait;
-            match event {
-                Event::WindowEvent { event, .. } => match event {
-                    WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
-                    _ => (),
-                },
-                Event::LoopDestroyed => {
-                    // free gl resources on loop end
-                    for scene in &scenes {
-                        scene.drop();
-                    }
+    // proxy for waking shared event loop from other threads, triggering Renderer::update
+    pub fn proxy() -> Result<EventLoopProxy<()>, WindowError> {
+        if PROXY.with(|proxy| proxy.borrow().is_none()) {
+            with_event_loop(|_| ())?; // creates loop along with its proxy
+        }
+        Ok(PROXY.with(|proxy| proxy.borrow().clone().unwrap()))
+    }
+
+    // make window context target of following gl calls
+    pub fn make_current(&self) -> Result<(), WindowError> {
+        Ok(self.ctx.make_current()?)
+    }
+
+    fn with_ctx<T>(&self, f: impl FnOnce(&GlContext) -> T) -> T {
+        f(self.ctx.0.borrow().as_ref().unwrap())
+    }
+
+    fn id(&self) -> WindowId {
+        self.with_ctx(|ctx| ctx.window().id())
+    }
+
+    // physical px per logical px, e.g. 2 on hidpi displays
+    pub fn scale_factor(&self) -> f64 {
+        self.with_ctx(|ctx| ctx.window().scale_factor())
+    }
+
+    fn request_redraw(&self) {
+        self.with_ctx(|ctx| ctx.window().request_redraw());
+    }
+}
+
+// open windows with their renderers, driven by shared event loop
+pub struct WindowSet<R: Renderer> {
+    windows: Vec<(Window, R)>,
+}
+
+impl<R: Renderer> WindowSet<R> {
+    pub fn new() -> Self {
+        Self { windows: vec![] }
+    }
+
+    pub fn push(&mut self, window: Window, renderer: R) {
+        window.request_redraw();
+        self.windows.push((window, renderer));
+    }
+
+    // block until all windows are closed, returning early with first draw error
+    pub fn run(&mut self) -> Result<(), R::Error> {
+        self.run_loop(ControlFlow::Wait)
+    }
+
+    // handle pending events and redraws without blocking,
+    // returning false once all windows are closed
+    pub fn poll(&mut self) -> Result<bool, R::Error> {
+        self.run_loop(ControlFlow::Poll)?;
+        Ok(!self.windows.is_empty())
+    }
+
+    // windows stay open after draw error, so loop can be run again once handled
+    fn run_loop(&mut self, flow: ControlFlow) -> Result<(), R::Error> {
+        if self.windows.is_empty() {
+            return Ok(());
+        }
+        let mut error = None;
+        with_event_loop(|event_loop| {
+            event_loop.run_return(|event, _, control_flow| {
+                if error.is_some() {
+                    *control_flow = ControlFlow::Exit;
+                    return;
                 }
-                Event::RedrawRequested(_) => {
-                    unsafe {
-                        gl::Clear(gl::COLOR_BUFFER_BIT);
+                *control_flow = flow;
+                match event {
+                    Event::WindowEvent {
+                        event: WindowEvent::CloseRequested,
+                        window_id,
+                    } => self.close(window_id),
+                    Event::WindowEvent { event, window_id } => {
+                        if let Some((window, renderer)) = self.get(window_id) {
+                            if renderer.handle_event(&event) {
+                                window.request_redraw();
+                            }
+                        }
+                    }
+                    Event::UserEvent(()) => {
+                        for (window, renderer) in &mut self.windows {
+                            if renderer.update() {
+                                window.request_redraw();
+                            }
+                        }
                     }
-                    for scene in &scenes {
-                        scene.draw().unwrap();
+                    Event::RedrawRequested(window_id) => {
+                        if let Some((window, renderer)) = self.get(window_id) {
+                            if let Err(e) = Self::redraw(window, renderer) {
+                                error = Some(e);
+                                *control_flow = ControlFlow::Exit;
+                            }
+                        }
                     }
-                    self.ctx.swap_buffers().unwrap();
+                    // return to caller once pending events are handled when polling
+                    Event::RedrawEventsCleared if flow == ControlFlow::Poll => {
+                        *control_flow = ControlFlow::Exit
+                    }
+                    _ => (),
                 }
-                _ => (),
-            }
-        })
+                if self.windows.is_empty() {
+                    *control_flow = ControlFlow::Exit;
+                }
+            });
+        })?;
+        error.map_or(Ok(()), Err)
+    }
+
+    fn redraw(window: &mut Window, renderer: &mut R) -> Result<(), R::Error> {
+        window.make_current()?;
+        unsafe {
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
+        renderer.draw()?;
+        window
+            .with_ctx(|ctx| ctx.swap_buffers())
+            .map_err(WindowError::from)?;
+        Ok(())
+    }
+
+    fn get(&mut self, id: WindowId) -> Option<&mut (Window, R)> {
+        self.windows
+            .iter_mut()
+            .find(|(window, _)| window.id() == id)
+    }
+
+    // window is destroyed once renderer has freed its gl resources in window context
+    fn close(&mut self, id: WindowId) {
+        self.windows.retain(|(window, _)| window.id() != id);
     }
 }
 
+// user defined state updated and drawn by window event loop
+pub trait Renderer {
+    type Error: std::fmt::Debug + From<WindowError>;
+
+    // handle window input, returning true if window should be redrawn
+    fn handle_event(&mut self, event: &WindowEvent) -> bool;
+
+    // apply data sent from other threads on proxy wake up, returning true if window should be redrawn
+    fn update(&mut self) -> bool;
+
+    fn draw(&mut self) -> Result<(), Self::Error>;
+}
+
 pub struct Shader {
     pub id: GLuint,
+    ctx: Option<Context>,
 }
 
 impl Shader {
@@ -79,6 +291,7 @@ impl Shader {
         unsafe {
             shader = Self {
                 id: gl::CreateShader(shader_type),
+                ctx: Context::current(),
             };
             gl::ShaderSource(shader.id, 1, &source_code.as_ptr(), ptr::null());
             gl::CompileShader(shader.id);
@@ -114,24 +327,34 @@ impl Shader {
 }
 
 impl Drop for Shader {
-    fn drop(&self) {
-        unsafe {
-            gl::DeleteShader(self.id);
-        }
+    fn drop(&mut self) {
+        free_in(&self.ctx, || unsafe { gl::DeleteShader(self.id) });
     }
 }
 
 pub struct Program {
     pub id: GLuint,
+    ctx: Option<Context>,
+    // reflected once after linking, locations stored at same index as variable,
+    // with location of each array element for uniforms
+    attributes: Vec<ActiveVariable>,
+    attribute_locations: Vec<GLint>,
+    uniforms: Vec<ActiveVariable>,
+    uniform_locations: Vec<Vec<GLint>>,
 }
 
 impl Program {
     pub fn new(vertex_shader: &Shader, fragment_shader: &Shader) -> Result<Self, ProgramError> {
         // link shaders into program
-        let program: Self;
+        let mut program: Self;
         unsafe {
             program = Self {
                 id: gl::CreateProgram(),
+                ctx: Context::current(),
+                attributes: Vec::new(),
+                attribute_locations: Vec::new(),
+                uniforms: Vec::new(),
+                uniform_locations: Vec::new(),
             };
             gl::AttachShader(program.id, vertex_shader.id);
             gl::AttachShader(program.id, fragment_shader.id);
@@ -144,6 +367,7 @@ impl Program {
             gl::GetProgramiv(program.id, gl::LINK_STATUS, &mut success);
         }
         if success == 1 {
+            program.reflect()?;
             Ok(program)
         } else {
             // get program info log and throw error on linking failure
@@ -170,27 +394,168 @@ impl Program {
     pub fn new_from_files(vertex_file: &str, fragment_file: &str) -> Result<Self, ProgramError> {
         let vertex_shader = Shader::new(vertex_file, gl::VERTEX_SHADER)?;
         let fragment_shader = Shader::new(fragment_file, gl::FRAGMENT_SHADER)?;
-        let result = Self::new(&vertex_shader, &fragment_shader);
+        // shaders freed once dropped after linking
+        Self::new(&vertex_shader, &fragment_shader)
+    }
 
-        // free unneccesary shader resources after linking
-        vertex_shader.drop();
-        fragment_shader.drop();
+    // cache attributes and uniforms used by linked program with their locations,
+    // unused ones are optimized out by driver
+    fn reflect(&mut self) -> Result<(), ProgramError> {
+        let attributes = self.active_variables(
+            gl::ACTIVE_ATTRIBUTES,
+            gl::ACTIVE_ATTRIBUTE_MAX_LENGTH,
+            gl::GetActiveAttrib,
+        );
+        for attribute in attributes {
+            let cname = CString::new(attribute.name.as_str())?;
+            let location = unsafe { gl::GetAttribLocation(self.id, cname.as_ptr()) };
+            self.attributes.push(attribute);
+            self.attribute_locations.push(location);
+        }
+        // arrays are listed once with [0] suffix, elements located separately
+        let uniforms = self.active_variables(
+            gl::ACTIVE_UNIFORMS,
+            gl::ACTIVE_UNIFORM_MAX_LENGTH,
+            gl::GetActiveUniform,
+        );
+        for uniform in uniforms {
+            let base = uniform.name.strip_suffix("[0]");
+            let locations = (0..uniform.size)
+                .map(|i| {
+                    let name = match base {
+                        Some(base) => format!("{}[{}]", base, i),
+                        None => uniform.name.clone(),
+                    };
+                    let cname = CString::new(name)?;
+                    Ok(unsafe { gl::GetUniformLocation(self.id, cname.as_ptr()) })
+                })
+                .collect::<Result<_, ProgramError>>()?;
+            self.uniforms.push(uniform);
+            self.uniform_locations.push(locations);
+        }
+        Ok(())
+    }
 
-        // return result of default constructor
-        result
+    fn active_variables(
+        &self,
+        count_param: GLenum,
+        length_param: GLenum,
+        get_active: unsafe fn(
+            GLuint,
+            GLuint,
+            GLsizei,
+            *mut GLsizei,
+            *mut GLint,
+            *mut GLenum,
+            *mut GLchar,
+        ),
+    ) -> Vec<ActiveVariable> {
+        let mut count: GLint = 0;
+        let mut max_length: GLint = 0;
+        unsafe {
+            gl::GetProgramiv(self.id, count_param, &mut count);
+            gl::GetProgramiv(self.id, length_param, &mut max_length);
+        }
+        (0..count as GLuint)
+            .map(|index| {
+                let mut buffer = vec![0u8; max_length.max(1) as usize];
+                let (mut length, mut size, mut gl_type) = (0, 0, 0);
+                unsafe {
+                    get_active(
+                        self.id,
+                        index,
+                        max_length,
+                        &mut length,
+                        &mut size,
+                        &mut gl_type,
+                        buffer.as_mut_ptr() as *mut _,
+                    );
+                }
+                buffer.truncate(length as usize);
+                ActiveVariable {
+                    name: String::from_utf8_lossy(&buffer).into_owned(),
+                    gl_type,
+                    size,
+                }
+            })
+            .collect()
+    }
+
+    // type and location of active uniform, error if name is not used by program,
+    // element name like arr[2] has size of array elements from it onward
+    pub fn get_active_uniform(&self, name: &str) -> Result<(ActiveVariable, GLint), ProgramError> {
+        let (base, element) = split_element(name);
+        let found = self.uniforms.iter().position(|uniform| {
+            // array uniforms are reported with first element suffix
+            let array = uniform.name.strip_suffix("[0]");
+            uniform.name == name || array == Some(base)
+        });
+        let found = found.map(|i| (&self.uniforms[i], &self.uniform_locations[i]));
+        match found {
+            Some((uniform, locations)) if element < locations.len() => Ok((
+                ActiveVariable {
+                    name: name.to_string(),
+                    gl_type: uniform.gl_type,
+                    size: uniform.size - element as GLint,
+                },
+                locations[element],
+            )),
+            _ => Err(ProgramError::MissingUniform {
+                name: name.to_string(),
+                active: self.uniforms.iter().map(|u| u.name.clone()).collect(),
+            }),
+        }
+    }
+
+    pub fn get_uniform_location(&self, name: &str) -> Result<GLint, ProgramError> {
+        Ok(self.get_active_uniform(name)?.1)
     }
 
     pub fn get_attrib_location(&self, attrib: &str) -> Result<GLuint, ProgramError> {
-        let attrib = CString::new(attrib)?;
-        unsafe { Ok(gl::GetAttribLocation(self.id, attrib.as_ptr()) as GLuint) }
+        // misspelled or optimized out attribute has no location, invalid as vao index
+        let found = self.attributes.iter().position(|a| a.name == attrib);
+        match found.map(|i| self.attribute_locations[i]) {
+            Some(location) if location >= 0 => Ok(location as GLuint),
+            _ => Err(ProgramError::MissingAttribute {
+                name: attrib.to_string(),
+                active: self.attributes.iter().map(|a| a.name.clone()).collect(),
+            }),
+        }
     }
+
+    // attributes used by linked program, e.g. to check vertex layout against shader
+    #[allow(dead_code)]
+    pub fn active_attributes(&self) -> &[ActiveVariable] {
+        &self.attributes
+    }
+
+    // uniforms used by linked program, arrays listed once with [0] suffix
+    #[allow(dead_code)]
+    pub fn active_uniforms(&self) -> &[ActiveVariable] {
+        &self.uniforms
+    }
+}
+
+// attribute or uniform reported by program, with size as array length
+#[derive(Clone, Debug)]
+pub struct ActiveVariable {
+    pub name: String,
+    pub gl_type: GLenum,
+    pub size: GLint,
+}
+
+// array name and element index of uniform name like arr[2], whole name otherwise
+fn split_element(name: &str) -> (&str, usize) {
+    let element = name
+        .strip_suffix(']')
+        .and_then(|name| name.rsplit_once('['))
+        .and_then(|(base, index)| Some((base, index.parse().ok()?)));
+    element.unwrap_or((name, 0))
 }
 
 impl Drop for Program {
-    fn drop(&self) {
-        unsafe {
-            gl::DeleteProgram(self.id);
-        }
+    fn drop(&mut self) {
+        free_in(&self.ctx, || unsafe { gl::DeleteProgram(self.id) });
     }
 }
 
@@ -204,6 +569,7 @@ impl Bind for Program {
 
 pub struct Buffer {
     pub id: GLuint,
+    ctx: Option<Context>,
 }
 
 impl Buffer {
@@ -212,7 +578,10 @@ impl Buffer {
         unsafe {
             gl::GenBuffers(1, &mut id);
         }
-        Self { id }
+        Self {
+            id,
+            ctx: Context::current(),
+        }
     }
 
     pub fn set_data<D>(&self, data: &[D], draw_type: GLuint) {
@@ -233,16 +602,45 @@ impl Buffer {
         buffer.set_data(data, draw_type);
         buffer
     }
-}
 
-impl Drop for Buffer {
-    fn drop(&self) {
+    // allocate storage for capacity elements, to be filled with set_sub_data
+    pub fn new_with_capacity<D>(capacity: usize, draw_type: GLuint) -> Self {
+        let buffer = Buffer::new();
+        buffer.bind();
         unsafe {
-            gl::DeleteBuffers(1, [self.id].as_ptr());
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (capacity * std::mem::size_of::<D>()) as GLsizeiptr,
+                ptr::null(),
+                draw_type,
+            );
+        }
+        buffer
+    }
+
+    // overwrite elements from offset onwards without reallocating storage
+    pub fn set_sub_data<D>(&self, offset: usize, data: &[D]) {
+        self.bind();
+        unsafe {
+            let (_, bytes, _) = data.align_to::<u8>();
+            gl::BufferSubData(
+                gl::ARRAY_BUFFER,
+                (offset * std::mem::size_of::<D>()) as GLintptr,
+                bytes.len() as GLsizeiptr,
+                bytes.as_ptr() as *const _,
+            );
         }
     }
 }
 
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        free_in(&self.ctx, || unsafe {
+            gl::DeleteBuffers(1, [self.id].as_ptr())
+        });
+    }
+}
+
 impl Bind for Buffer {
     fn bind(&self) {
         unsafe {
@@ -253,6 +651,8 @@ impl Bind for Buffer {
 
 pub struct VertexArray {
     pub id: GLuint,
+    elements: bool, // element buffer bound for indexed draws
+    ctx: Option<Context>,
 }
 
 impl VertexArray {
@@ -261,7 +661,11 @@ impl VertexArray {
         unsafe {
             gl::GenVertexArrays(1, &mut id);
         }
-        Self { id }
+        Self {
+            id,
+            elements: false,
+            ctx: Context::current(),
+        }
     }
 
     pub fn set_attribute<V: Sized>(&self, location: GLuint, size: GLint, offset_ind: i32) {
@@ -273,14 +677,35 @@ impl VertexArray {
             gl::EnableVertexAttribArray(location);
         }
     }
-}
 
-impl Drop for VertexArray {
-    fn drop(&self) {
+    // attribute advanced once per instance rather than per vertex in instanced draws
+    pub fn set_instance_attribute<V: Sized>(&self, location: GLuint, size: GLint, offset_ind: i32) {
+        self.set_attribute::<V>(location, size, offset_ind);
         unsafe {
-            gl::DeleteVertexArrays(1, [self.id].as_ptr());
+            gl::VertexAttribDivisor(location, 1);
         }
     }
+
+    // u32 indices used by indexed draws, element binding is stored in vao
+    pub fn set_elements(&mut self, buffer: &Buffer) {
+        self.bind();
+        unsafe {
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, buffer.id);
+        }
+        self.elements = true;
+    }
+
+    pub fn has_elements(&self) -> bool {
+        self.elements
+    }
+}
+
+impl Drop for VertexArray {
+    fn drop(&mut self) {
+        free_in(&self.ctx, || unsafe {
+            gl::DeleteVertexArrays(1, [self.id].as_ptr())
+        });
+    }
 }
 
 impl Bind for VertexArray {
@@ -291,9 +716,12 @@ impl Bind for VertexArray {
     }
 }
 
-#[derive(Copy, Clone)]
+// shared between font map and scenes drawing its text, freed with last owner
+pub type SharedTexture = Rc<Texture>;
+
 pub struct Texture {
     id: GLuint,
+    ctx: Option<Context>,
 }
 
 impl Texture {
@@ -318,7 +746,10 @@ impl Texture {
                 &data[0] as *const _ as *const std::ffi::c_void,
             );
         }
-        Self { id }
+        Self {
+            id,
+            ctx: Context::current(),
+        }
     }
 
     pub fn new_blank(width: i32, height: i32) -> Self {
@@ -328,10 +759,10 @@ impl Texture {
 }
 
 impl Drop for Texture {
-    fn drop(&self) {
-        unsafe {
-            gl::DeleteTextures(1, [self.id].as_ptr());
-        }
+    fn drop(&mut self) {
+        free_in(&self.ctx, || unsafe {
+            gl::DeleteTextures(1, [self.id].as_ptr())
+        });
     }
 }
 
@@ -345,11 +776,12 @@ impl Bind for Texture {
 
 pub struct TextureFramebuffer {
     id: GLuint,
-    pub texture: Texture,
+    pub texture: SharedTexture, // kept by rendered texture users after framebuffer is freed
     pub width: i32,
     pub height: i32,
     window_width: i32,
     window_height: i32,
+    ctx: Option<Context>,
 }
 
 impl TextureFramebuffer {
@@ -360,7 +792,7 @@ impl TextureFramebuffer {
         window_height: i32,
     ) -> Result<Self, FramebufferError> {
         let mut id: GLuint = 0;
-        let texture = Texture::new_blank(width, height);
+        let texture = Rc::new(Texture::new_blank(width, height));
         unsafe {
             gl::BindTexture(gl::TEXTURE_2D, 0); // unbind fb texture
             gl::GenFramebuffers(1, &mut id);
@@ -373,6 +805,8 @@ impl TextureFramebuffer {
                 0,
             );
             if gl::CheckFramebufferStatus(gl::FRAMEBUFFER) != gl::FRAMEBUFFER_COMPLETE {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+                gl::DeleteFramebuffers(1, [id].as_ptr());
                 return Err(FramebufferError::CreationError);
             }
             gl::BindFramebuffer(gl::FRAMEBUFFER, 0); // bind default fb
@@ -384,6 +818,7 @@ impl TextureFramebuffer {
             height,
             window_width,
             window_height,
+            ctx: Context::current(),
         })
     }
 
@@ -396,10 +831,10 @@ impl TextureFramebuffer {
 }
 
 impl Drop for TextureFramebuffer {
-    fn drop(&self) {
-        unsafe {
-            gl::DeleteFramebuffers(1, [self.id].as_ptr());
-        }
+    fn drop(&mut self) {
+        free_in(&self.ctx, || unsafe {
+            gl::DeleteFramebuffers(1, [self.id].as_ptr())
+        });
     }
 }
 
@@ -412,46 +847,420 @@ impl Bind for TextureFramebuffer {
     }
 }
 
-pub struct Uniform {
-    location: i32,
-    value: Vec<f32>,
+// uniform value with one element per array entry, single values have one element
+#[derive(Clone, Debug, PartialEq)]
+pub enum UniformValue {
+    Float(Vec<f32>),
+    Vec2(Vec<[f32; 2]>),
+    Vec3(Vec<[f32; 3]>),
+    Vec4(Vec<[f32; 4]>),
+    Int(Vec<i32>),
+    IVec2(Vec<[i32; 2]>),
+    IVec3(Vec<[i32; 3]>),
+    IVec4(Vec<[i32; 4]>),
+    UInt(Vec<u32>),
+    UVec2(Vec<[u32; 2]>),
+    UVec3(Vec<[u32; 3]>),
+    UVec4(Vec<[u32; 4]>),
+    Bool(Vec<bool>),
+    BVec2(Vec<[bool; 2]>),
+    BVec3(Vec<[bool; 3]>),
+    BVec4(Vec<[bool; 4]>),
+    Mat2(Vec<[f32; 4]>), // column major
+    Mat3(Vec<[f32; 9]>),
+    Mat4(Vec<[f32; 16]>),
+    Sampler(Vec<i32>), // texture unit
 }
 
-impl Uniform {
-    pub fn new(program: &Program, name: &str, value: &[f32]) -> Result<Self, UniformError> {
-        let cname = CString::new(name)?;
-        let location: i32;
+impl UniformValue {
+    pub fn len(&self) -> usize {
+        match self {
+            UniformValue::Float(v) => v.len(),
+            UniformValue::Vec2(v) => v.len(),
+            UniformValue::Vec3(v) => v.len(),
+            UniformValue::Vec4(v) => v.len(),
+            UniformValue::Int(v) => v.len(),
+            UniformValue::IVec2(v) => v.len(),
+            UniformValue::IVec3(v) => v.len(),
+            UniformValue::IVec4(v) => v.len(),
+            UniformValue::UInt(v) => v.len(),
+            UniformValue::UVec2(v) => v.len(),
+            UniformValue::UVec3(v) => v.len(),
+            UniformValue::UVec4(v) => v.len(),
+            UniformValue::Bool(v) => v.len(),
+            UniformValue::BVec2(v) => v.len(),
+            UniformValue::BVec3(v) => v.len(),
+            UniformValue::BVec4(v) => v.len(),
+            UniformValue::Mat2(v) => v.len(),
+            UniformValue::Mat3(v) => v.len(),
+            UniformValue::Mat4(v) => v.len(),
+            UniformValue::Sampler(v) => v.len(),
+        }
+    }
+
+    // glsl type name, as used in error messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            UniformValue::Float(_) => "float",
+            UniformValue::Vec2(_) => "vec2",
+            UniformValue::Vec3(_) => "vec3",
+            UniformValue::Vec4(_) => "vec4",
+            UniformValue::Int(_) => "int",
+            UniformValue::IVec2(_) => "ivec2",
+            UniformValue::IVec3(_) => "ivec3",
+            UniformValue::IVec4(_) => "ivec4",
+            UniformValue::UInt(_) => "uint",
+            UniformValue::UVec2(_) => "uvec2",
+            UniformValue::UVec3(_) => "uvec3",
+            UniformValue::UVec4(_) => "uvec4",
+            UniformValue::Bool(_) => "bool",
+            UniformValue::BVec2(_) => "bvec2",
+            UniformValue::BVec3(_) => "bvec3",
+            UniformValue::BVec4(_) => "bvec4",
+            UniformValue::Mat2(_) => "mat2",
+            UniformValue::Mat3(_) => "mat3",
+            UniformValue::Mat4(_) => "mat4",
+            UniformValue::Sampler(_) => "sampler",
+        }
+    }
+
+    // whether value can be set on active uniform of given gl type
+    fn matches(&self, gl_type: GLenum) -> bool {
+        match self {
+            UniformValue::Float(_) => gl_type == gl::FLOAT,
+            UniformValue::Vec2(_) => gl_type == gl::FLOAT_VEC2,
+            UniformValue::Vec3(_) => gl_type == gl::FLOAT_VEC3,
+            UniformValue::Vec4(_) => gl_type == gl::FLOAT_VEC4,
+            UniformValue::Int(_) => gl_type == gl::INT,
+            UniformValue::IVec2(_) => gl_type == gl::INT_VEC2,
+            UniformValue::IVec3(_) => gl_type == gl::INT_VEC3,
+            UniformValue::IVec4(_) => gl_type == gl::INT_VEC4,
+            UniformValue::UInt(_) => gl_type == gl::UNSIGNED_INT,
+            UniformValue::UVec2(_) => gl_type == gl::UNSIGNED_INT_VEC2,
+            UniformValue::UVec3(_) => gl_type == gl::UNSIGNED_INT_VEC3,
+            UniformValue::UVec4(_) => gl_type == gl::UNSIGNED_INT_VEC4,
+            UniformValue::Bool(_) => gl_type == gl::BOOL,
+            UniformValue::BVec2(_) => gl_type == gl::BOOL_VEC2,
+            UniformValue::BVec3(_) => gl_type == gl::BOOL_VEC3,
+            UniformValue::BVec4(_) => gl_type == gl::BOOL_VEC4,
+            UniformValue::Mat2(_) => gl_type == gl::FLOAT_MAT2,
+            UniformValue::Mat3(_) => gl_type == gl::FLOAT_MAT3,
+            UniformValue::Mat4(_) => gl_type == gl::FLOAT_MAT4,
+            UniformValue::Sampler(_) => SAMPLER_TYPES.contains(&gl_type),
+        }
+    }
+
+    // upload value to uniform at location of currently bound program
+    fn set(&self, location: GLint) {
+        let count = self.len() as GLsizei;
         unsafe {
-            location = gl::GetUniformLocation(program.id, cname.as_ptr());
+            match self {
+                UniformValue::Float(v) => gl::Uniform1fv(location, count, v.as_ptr()),
+                UniformValue::Vec2(v) => gl::Uniform2fv(location, count, v.as_ptr().cast()),
+                UniformValue::Vec3(v) => gl::Uniform3fv(location, count, v.as_ptr().cast()),
+                UniformValue::Vec4(v) => gl::Uniform4fv(location, count, v.as_ptr().cast()),
+                UniformValue::Int(v) => gl::Uniform1iv(location, count, v.as_ptr()),
+                UniformValue::IVec2(v) => gl::Uniform2iv(location, count, v.as_ptr().cast()),
+                UniformValue::IVec3(v) => gl::Uniform3iv(location, count, v.as_ptr().cast()),
+                UniformValue::IVec4(v) => gl::Uniform4iv(location, count, v.as_ptr().cast()),
+                UniformValue::UInt(v) => gl::Uniform1uiv(location, count, v.as_ptr()),
+                UniformValue::UVec2(v) => gl::Uniform2uiv(location, count, v.as_ptr().cast()),
+                UniformValue::UVec3(v) => gl::Uniform3uiv(location, count, v.as_ptr().cast()),
+                UniformValue::UVec4(v) => gl::Uniform4uiv(location, count, v.as_ptr().cast()),
+                // bools set as ints, one per component
+                UniformValue::Bool(v) => gl::Uniform1iv(location, count, bool_ints(v).as_ptr()),
+                UniformValue::BVec2(v) => {
+                    gl::Uniform2iv(location, count, bool_ints(v.concat()).as_ptr())
+                }
+                UniformValue::BVec3(v) => {
+                    gl::Uniform3iv(location, count, bool_ints(v.concat()).as_ptr())
+                }
+                UniformValue::BVec4(v) => {
+                    gl::Uniform4iv(location, count, bool_ints(v.concat()).as_ptr())
+                }
+                UniformValue::Mat2(v) => {
+                    gl::UniformMatrix2fv(location, count, gl::FALSE, v.as_ptr().cast())
+                }
+                UniformValue::Mat3(v) => {
+                    gl::UniformMatrix3fv(location, count, gl::FALSE, v.as_ptr().cast())
+                }
+                UniformValue::Mat4(v) => {
+                    gl::UniformMatrix4fv(location, count, gl::FALSE, v.as_ptr().cast())
+                }
+                UniformValue::Sampler(v) => gl::Uniform1iv(location, count, v.as_ptr()),
+            }
         }
-        let value = value.to_vec();
-        Ok(Self { location, value })
     }
+}
 
-    pub fn set(&self) -> Result<(), UniformError> {
-        match self.value.len() {
-            1 => unsafe { gl::Uniform1f(self.location, self.value[0]) },
-            2 => unsafe { gl::Uniform2fv(self.location, 1, &self.value[0]) },
-            3 => unsafe { gl::Uniform3fv(self.location, 1, &self.value[0]) },
-            4 => unsafe { gl::Uniform4fv(self.location, 1, &self.value[0]) },
-            16 => unsafe { gl::UniformMatrix4fv(self.location, 1, gl::FALSE, &self.value[0]) },
-            _ => return Err(UniformError::Length),
+// single values from plain arrays, length 4 taken as vec4 and 16 as mat4
+impl From<&[f32; 1]> for UniformValue {
+    fn from(value: &[f32; 1]) -> Self {
+        UniformValue::Float(value.to_vec())
+    }
+}
+impl From<&[f32; 2]> for UniformValue {
+    fn from(value: &[f32; 2]) -> Self {
+        UniformValue::Vec2(vec![*value])
+    }
+}
+impl From<&[f32; 3]> for UniformValue {
+    fn from(value: &[f32; 3]) -> Self {
+        UniformValue::Vec3(vec![*value])
+    }
+}
+impl From<&[f32; 4]> for UniformValue {
+    fn from(value: &[f32; 4]) -> Self {
+        UniformValue::Vec4(vec![*value])
+    }
+}
+impl From<&[f32; 16]> for UniformValue {
+    fn from(value: &[f32; 16]) -> Self {
+        UniformValue::Mat4(vec![*value])
+    }
+}
+impl From<&[i32; 2]> for UniformValue {
+    fn from(value: &[i32; 2]) -> Self {
+        UniformValue::IVec2(vec![*value])
+    }
+}
+impl From<&[i32; 3]> for UniformValue {
+    fn from(value: &[i32; 3]) -> Self {
+        UniformValue::IVec3(vec![*value])
+    }
+}
+impl From<&[i32; 4]> for UniformValue {
+    fn from(value: &[i32; 4]) -> Self {
+        UniformValue::IVec4(vec![*value])
+    }
+}
+impl From<&[u32; 2]> for UniformValue {
+    fn from(value: &[u32; 2]) -> Self {
+        UniformValue::UVec2(vec![*value])
+    }
+}
+impl From<&[u32; 3]> for UniformValue {
+    fn from(value: &[u32; 3]) -> Self {
+        UniformValue::UVec3(vec![*value])
+    }
+}
+impl From<&[u32; 4]> for UniformValue {
+    fn from(value: &[u32; 4]) -> Self {
+        UniformValue::UVec4(vec![*value])
+    }
+}
+impl From<&[bool; 2]> for UniformValue {
+    fn from(value: &[bool; 2]) -> Self {
+        UniformValue::BVec2(vec![*value])
+    }
+}
+impl From<&[bool; 3]> for UniformValue {
+    fn from(value: &[bool; 3]) -> Self {
+        UniformValue::BVec3(vec![*value])
+    }
+}
+impl From<&[bool; 4]> for UniformValue {
+    fn from(value: &[bool; 4]) -> Self {
+        UniformValue::BVec4(vec![*value])
+    }
+}
+// smaller matrices given as columns
+impl From<&[[f32; 2]; 2]> for UniformValue {
+    fn from(value: &[[f32; 2]; 2]) -> Self {
+        let [a, b] = *value;
+        UniformValue::Mat2(vec![[a[0], a[1], b[0], b[1]]])
+    }
+}
+impl From<&[[f32; 3]; 3]> for UniformValue {
+    fn from(value: &[[f32; 3]; 3]) -> Self {
+        let mut mat = [0.0; 9];
+        mat.copy_from_slice(&value.concat());
+        UniformValue::Mat3(vec![mat])
+    }
+}
+impl From<f32> for UniformValue {
+    fn from(value: f32) -> Self {
+        UniformValue::Float(vec![value])
+    }
+}
+impl From<i32> for UniformValue {
+    fn from(value: i32) -> Self {
+        UniformValue::Int(vec![value])
+    }
+}
+impl From<u32> for UniformValue {
+    fn from(value: u32) -> Self {
+        UniformValue::UInt(vec![value])
+    }
+}
+impl From<bool> for UniformValue {
+    fn from(value: bool) -> Self {
+        UniformValue::Bool(vec![value])
+    }
+}
+impl From<TextureUnit> for UniformValue {
+    fn from(value: TextureUnit) -> Self {
+        UniformValue::Sampler(vec![value.0])
+    }
+}
+
+// texture unit read by sampler uniform, distinguishing it from plain int value
+#[derive(Copy, Clone, Debug)]
+pub struct TextureUnit(pub i32);
+
+fn bool_ints(values: impl AsRef<[bool]>) -> Vec<i32> {
+    values.as_ref().iter().map(|&b| b as i32).collect()
+}
+
+pub struct Uniform {
+    name: String,
+    location: i32,
+    value: UniformValue,
+    active: ActiveVariable,
+}
+
+impl Uniform {
+    pub fn new(
+        program: &Program,
+        name: &str,
+        value: impl Into<UniformValue>,
+    ) -> Result<Self, UniformError> {
+        let (active, location) = program.get_active_uniform(name)?;
+        let mut uniform = Self {
+            name: name.to_string(),
+            location,
+            value: UniformValue::Float(vec![]),
+            active,
         };
+        uniform.set_value(value)?;
+        Ok(uniform)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // replace value, checking it against type and size declared in shader
+    pub fn set_value(&mut self, value: impl Into<UniformValue>) -> Result<(), UniformError> {
+        let value = value.into();
+        self.check(&value)?;
+        self.value = value;
+        Ok(())
+    }
+
+    // whether value can be set on uniform, without replacing current value
+    pub fn check(&self, value: &UniformValue) -> Result<(), UniformError> {
+        if value.len() == 0 {
+            return Err(UniformError::Empty(self.name.clone()));
+        }
+        let ActiveVariable { gl_type, size, .. } = self.active;
+        if !value.matches(gl_type) {
+            return Err(UniformError::Type {
+                name: self.name.clone(),
+                expected: gl_type_name(gl_type),
+                found: value.type_name(),
+            });
+        }
+        if value.len() > size as usize {
+            return Err(UniformError::Count {
+                name: self.name.clone(),
+                size: size as usize,
+                count: value.len(),
+            });
+        }
         Ok(())
     }
+
+    pub fn set(&self) {
+        self.value.set(self.location);
+    }
 }
 
-// traits to update gl context state
-pub trait Drop {
-    fn drop(&self);
+// glsl name of active uniform type, as used in error messages
+fn gl_type_name(gl_type: GLenum) -> &'static str {
+    match gl_type {
+        gl::FLOAT => "float",
+        gl::FLOAT_VEC2 => "vec2",
+        gl::FLOAT_VEC3 => "vec3",
+        gl::FLOAT_VEC4 => "vec4",
+        gl::INT => "int",
+        gl::INT_VEC2 => "ivec2",
+        gl::INT_VEC3 => "ivec3",
+        gl::INT_VEC4 => "ivec4",
+        gl::UNSIGNED_INT => "uint",
+        gl::UNSIGNED_INT_VEC2 => "uvec2",
+        gl::UNSIGNED_INT_VEC3 => "uvec3",
+        gl::UNSIGNED_INT_VEC4 => "uvec4",
+        gl::BOOL => "bool",
+        gl::BOOL_VEC2 => "bvec2",
+        gl::BOOL_VEC3 => "bvec3",
+        gl::BOOL_VEC4 => "bvec4",
+        gl::FLOAT_MAT2 => "mat2",
+        gl::FLOAT_MAT3 => "mat3",
+        gl::FLOAT_MAT4 => "mat4",
+        t if SAMPLER_TYPES.contains(&t) => "sampler",
+        _ => "unsupported type",
+    }
 }
+
+// traits to update gl context state
 pub trait Bind {
     fn bind(&self);
 }
 
+// all sampler types of gl 3.3, set with texture unit index
+static SAMPLER_TYPES: [GLenum; 36] = [
+    gl::SAMPLER_1D,
+    gl::SAMPLER_2D,
+    gl::SAMPLER_3D,
+    gl::SAMPLER_CUBE,
+    gl::SAMPLER_1D_SHADOW,
+    gl::SAMPLER_2D_SHADOW,
+    gl::SAMPLER_CUBE_SHADOW,
+    gl::SAMPLER_1D_ARRAY,
+    gl::SAMPLER_2D_ARRAY,
+    gl::SAMPLER_1D_ARRAY_SHADOW,
+    gl::SAMPLER_2D_ARRAY_SHADOW,
+    gl::SAMPLER_2D_MULTISAMPLE,
+    gl::SAMPLER_2D_MULTISAMPLE_ARRAY,
+    gl::SAMPLER_BUFFER,
+    gl::SAMPLER_2D_RECT,
+    gl::SAMPLER_2D_RECT_SHADOW,
+    gl::INT_SAMPLER_1D,
+    gl::INT_SAMPLER_2D,
+    gl::INT_SAMPLER_3D,
+    gl::INT_SAMPLER_CUBE,
+    gl::INT_SAMPLER_1D_ARRAY,
+    gl::INT_SAMPLER_2D_ARRAY,
+    gl::INT_SAMPLER_2D_MULTISAMPLE,
+    gl::INT_SAMPLER_2D_MULTISAMPLE_ARRAY,
+    gl::INT_SAMPLER_BUFFER,
+    gl::INT_SAMPLER_2D_RECT,
+    gl::UNSIGNED_INT_SAMPLER_1D,
+    gl::UNSIGNED_INT_SAMPLER_2D,
+    gl::UNSIGNED_INT_SAMPLER_3D,
+    gl::UNSIGNED_INT_SAMPLER_CUBE,
+    gl::UNSIGNED_INT_SAMPLER_1D_ARRAY,
+    gl::UNSIGNED_INT_SAMPLER_2D_ARRAY,
+    gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE,
+    gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE_ARRAY,
+    gl::UNSIGNED_INT_SAMPLER_BUFFER,
+    gl::UNSIGNED_INT_SAMPLER_2D_RECT,
+];
+
 extern crate thiserror;
 use thiserror::Error;
 
+#[derive(Error, Debug)]
+pub enum WindowError {
+    #[error("{0}")]
+    Creation(#[from] CreationError),
+    #[error("{0}")]
+    Context(#[from] ContextError),
+    #[error("Event loop is running, windows cannot be opened or run from plot callbacks")]
+    Running,
+}
+
 #[derive(Error, Debug)]
 pub enum ShaderError {
     #[error("Compilation failed: {0}")]
@@ -474,6 +1283,10 @@ pub enum ProgramError {
     Shader(#[from] ShaderError),
     #[error{"{0}"}]
     Nul(#[from] NulError),
+    #[error("Attribute '{name}' not active in program, active: [{}]", active.join(", "))]
+    MissingAttribute { name: String, active: Vec<String> },
+    #[error("Uniform '{name}' not active in program, active: [{}]", active.join(", "))]
+    MissingUniform { name: String, active: Vec<String> },
 }
 
 #[derive(Error, Debug)]
@@ -485,7 +1298,19 @@ pub enum FramebufferError {
 #[derive(Error, Debug)]
 pub enum UniformError {
     #[error("{0}")]
-    Nul(#[from] NulError),
-    #[error("Invalid uniform length")]
-    Length,
+    Program(#[from] ProgramError),
+    #[error("Uniform '{0}' given empty value")]
+    Empty(String),
+    #[error("Uniform '{name}' declared as {expected} but given {found}")]
+    Type {
+        name: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+    #[error("Uniform '{name}' holds {size} elements but given {count}")]
+    Count {
+        name: String,
+        size: usize,
+        count: usize,
+    },
 }
@@ -1,14 +1,96 @@
 mod axis;
+mod camera;
+mod figure;
 mod gl_wrap;
+mod markup;
+mod measure;
 mod plot;
+mod plot2d;
 mod scene;
+mod select;
+mod series;
+mod stream;
 mod text;
 mod ticks;
 mod vertices;
-use plot::Plot;
+use figure::{Figure, PlotWindows};
+use plot::{Aspect, Plot};
+use plot2d::{Line2d, LineStyle};
+use series::Series;
+use ticks::{Formatter, Locator, TickStyle};
 
 fn main() {
     let mut plot = Plot::new("test", 800.0, 800.0).unwrap();
     plot.set_background_color([0.05, 0.05, 0.05]);
-    plot.display().unwrap();
+    plot.set_bounds(1.0, 1.0, 1.0).unwrap();
+    plot.axis.labels.x = "x_0".to_string();
+    plot.axis.labels.y = "\\sigma^2".to_string();
+    plot.axis.labels.z = "Temperature\n(K)".to_string();
+    plot.ticks.x.style = TickStyle::Grid;
+    plot.ticks.y.style = TickStyle::Grid;
+    let log = Locator::Log {
+        base: 10.0,
+        decades: 2,
+    };
+    plot.ticks.z.set_locator(log).unwrap();
+    plot.ticks.z.minor_divisions = 9; // multiples 2 to 9 of each power
+    plot.ticks.z.formatter = Formatter::Power(10.0);
+
+    // helix of points rising through box
+    let points = (0..60)
+        .map(|i| {
+            let t = i as f32 * 0.2;
+            [0.5 + 0.4 * t.cos(), i as f32 / 60.0, 0.5 + 0.4 * t.sin()]
+        })
+        .collect();
+    plot.add_series(Series::new("helix", points)).unwrap();
+    plot.on_select(|selected| println!("selected points: {:?}", selected[0]));
+    // print view on C press for reuse with Plot::set_camera
+    plot.on_camera_save(|state| match state.to_json() {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("{}", e),
+    });
+
+    // second window with two linked subplots sharing same event loop
+    let mut figure = Figure::new("scope", 1500.0, 500.0, 1, 3).unwrap();
+    figure.link_cameras(true);
+    let mut handles = vec![];
+    for col in 0..2 {
+        let cell = figure.plot(0, col).unwrap();
+        let handle = cell.add_series(Series::ring("scope", 200 * (col + 1)));
+        handles.push(handle.unwrap());
+        cell.set_auto_range(true);
+        cell.set_aspect(Aspect::Cube).unwrap();
+    }
+    // 2d inset of damped wave beside 3d views
+    let inset = figure.plot_2d(0, 2).unwrap();
+    inset.x_label = "t (s)".to_string();
+    inset.y_label = "amplitude".to_string();
+    let wave = (0..200)
+        .map(|i| {
+            let t = i as f32 * 0.05;
+            [t, (-0.3 * t).exp() * (2.0 * t).sin()]
+        })
+        .collect::<Vec<_>>();
+    let mut peaks = Line2d::new("peaks", wave.iter().step_by(8).copied().collect());
+    peaks.style = LineStyle::Scatter;
+    peaks.color = [1.0, 0.5, 0.2, 1.0];
+    inset.add_line(Line2d::new("wave", wave));
+    inset.add_line(peaks);
+
+    // scope of latest samples streamed from background thread while window is open
+    std::thread::spawn(move || {
+        for i in 0.. {
+            let t = i as f32 * 0.05;
+            let sample = [t, t.sin(), t.cos()];
+            if handles.iter().any(|h| h.append(vec![sample]).is_err()) {
+                break; // window closed
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+    });
+
+    let mut windows = PlotWindows::new(vec![plot]).unwrap();
+    windows.add_figure(figure).unwrap();
+    windows.run().unwrap();
 }
@@ -0,0 +1,274 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+// run of characters sharing the same scale and vertical offset
+pub struct TextRun {
+    pub text: String,
+    pub style: RunStyle,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RunStyle {
+    pub scale: f32, // glyph scale relative to base text
+    pub rise: f32,  // baseline offset as fraction of line height
+}
+
+impl RunStyle {
+    pub fn normal() -> Self {
+        Self {
+            scale: 1.0,
+            rise: 0.0,
+        }
+    }
+
+    fn superscript(&self) -> Self {
+        Self {
+            scale: self.scale * SCRIPT_SCALE,
+            rise: self.rise + SUPER_RISE * self.scale,
+        }
+    }
+
+    fn subscript(&self) -> Self {
+        Self {
+            scale: self.scale * SCRIPT_SCALE,
+            rise: self.rise - SUB_DROP * self.scale,
+        }
+    }
+}
+
+// parse single line of TeX-like markup into styled runs
+// supports ^{..} / _{..} groups, single char scripts (x^2),
+// named symbols (\alpha, \pm) and escapes (\_, \^, \{, \}, \\)
+pub fn parse(line: &str) -> Result<Vec<TextRun>, MarkupError> {
+    let mut parser = Parser {
+        chars: line.chars().peekable(),
+        runs: Vec::new(),
+    };
+    parser.parse_group(RunStyle::normal(), false)?;
+    Ok(parser.runs)
+}
+
+// escape characters with markup meaning so text is shown as written
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if let '\\' | '_' | '^' | '{' | '}' = c {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    runs: Vec<TextRun>,
+}
+
+impl<'a> Parser<'a> {
+    fn parse_group(&mut self, style: RunStyle, in_braces: bool) -> Result<(), MarkupError> {
+        loop {
+            match self.chars.next() {
+                None if in_braces => return Err(MarkupError::Unclosed),
+                None => return Ok(()),
+                Some('}') if in_braces => return Ok(()),
+                Some('}') => return Err(MarkupError::Unmatched),
+                Some('{') => self.parse_group(style, true)?,
+                Some('^') => self.parse_script(style.superscript())?,
+                Some('_') => self.parse_script(style.subscript())?,
+                Some('\\') => {
+                    let c = self.parse_command()?;
+                    self.push(c, style);
+                }
+                Some(c) => self.push(c, style),
+            }
+        }
+    }
+
+    // script applies to following braced group or single character
+    fn parse_script(&mut self, style: RunStyle) -> Result<(), MarkupError> {
+        match self.chars.next() {
+            Some('{') => self.parse_group(style, true),
+            Some('\\') => {
+                let c = self.parse_command()?;
+                self.push(c, style);
+                Ok(())
+            }
+            Some(c) => {
+                self.push(c, style);
+                Ok(())
+            }
+            None => Err(MarkupError::MissingScript),
+        }
+    }
+
+    // get character for escape sequence or named symbol following backslash
+    fn parse_command(&mut self) -> Result<char, MarkupError> {
+        match self.chars.peek() {
+            Some(c) if !c.is_ascii_alphabetic() => Ok(self.chars.next().unwrap()),
+            Some(_) => {
+                let mut name = String::new();
+                while let Some(&c) = self.chars.peek() {
+                    if !c.is_ascii_alphabetic() {
+                        break;
+                    }
+                    name.push(c);
+                    self.chars.next();
+                }
+                match SYMBOLS.iter().find(|(n, _)| *n == name) {
+                    Some(&(_, c)) => Ok(c),
+                    None => Err(MarkupError::Command(name)),
+                }
+            }
+            None => Err(MarkupError::Command(String::new())),
+        }
+    }
+
+    fn push(&mut self, c: char, style: RunStyle) {
+        match self.runs.last_mut() {
+            Some(run) if run.style == style => run.text.push(c),
+            _ => self.runs.push(TextRun {
+                text: c.to_string(),
+                style,
+            }),
+        }
+    }
+}
+
+static SCRIPT_SCALE: f32 = 0.7;
+static SUPER_RISE: f32 = 0.35;
+static SUB_DROP: f32 = 0.2;
+static SYMBOLS: [(&str, char); 41] = [
+    ("alpha", 'α'),
+    ("beta", 'β'),
+    ("gamma", 'γ'),
+    ("delta", 'δ'),
+    ("epsilon", 'ε'),
+    ("zeta", 'ζ'),
+    ("eta", 'η'),
+    ("theta", 'θ'),
+    ("iota", 'ι'),
+    ("kappa", 'κ'),
+    ("lambda", 'λ'),
+    ("mu", 'μ'),
+    ("nu", 'ν'),
+    ("xi", 'ξ'),
+    ("pi", 'π'),
+    ("rho", 'ρ'),
+    ("sigma", 'σ'),
+    ("tau", 'τ'),
+    ("upsilon", 'υ'),
+    ("phi", 'φ'),
+    ("chi", 'χ'),
+    ("psi", 'ψ'),
+    ("omega", 'ω'),
+    ("Gamma", 'Γ'),
+    ("Delta", 'Δ'),
+    ("Theta", 'Θ'),
+    ("Lambda", 'Λ'),
+    ("Xi", 'Ξ'),
+    ("Pi", 'Π'),
+    ("Sigma", 'Σ'),
+    ("Upsilon", 'Υ'),
+    ("Phi", 'Φ'),
+    ("Psi", 'Ψ'),
+    ("Omega", 'Ω'),
+    ("pm", '±'),
+    ("times", '×'),
+    ("cdot", '·'),
+    ("deg", '°'),
+    ("circ", '°'),
+    ("infty", '∞'),
+    ("partial", '∂'),
+];
+
+extern crate thiserror;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MarkupError {
+    #[error("Unknown markup command '\\{0}'")]
+    Command(String),
+    #[error("Unclosed '{{' in markup")]
+    Unclosed,
+    #[error("Unmatched '}}' in markup")]
+    Unmatched,
+    #[error("Missing script after '^' or '_'")]
+    MissingScript,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // text and style of each run, for comparing parsed lines
+    fn runs(line: &str) -> Vec<(String, RunStyle)> {
+        let runs = parse(line).unwrap();
+        runs.into_iter().map(|run| (run.text, run.style)).collect()
+    }
+
+    #[test]
+    fn plain_text_is_single_normal_run() {
+        assert_eq!(runs("x y"), vec![("x y".to_string(), RunStyle::normal())]);
+        assert!(parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn scripts_apply_to_group_or_single_char() {
+        let normal = RunStyle::normal();
+        let sup = normal.superscript();
+        let sub = normal.subscript();
+        assert_eq!(
+            runs("x^{10}y"),
+            vec![
+                ("x".to_string(), normal),
+                ("10".to_string(), sup),
+                ("y".to_string(), normal),
+            ]
+        );
+        assert_eq!(
+            runs("x_ij"),
+            vec![
+                ("x".to_string(), normal),
+                ("i".to_string(), sub),
+                ("j".to_string(), normal),
+            ]
+        );
+        // nested scripts shrink and shift again
+        let nested = runs("e^{x^2}");
+        assert_eq!(nested[2], ("2".to_string(), sup.superscript()));
+    }
+
+    #[test]
+    fn commands_give_symbols_and_escapes() {
+        assert_eq!(runs("\\alpha\\pm\\deg")[0].0, "α±°");
+        assert_eq!(runs("\\_\\^\\{\\}\\\\")[0].0, "_^{}\\");
+        assert_eq!(runs("10^\\infty")[1].0, "∞");
+        // braces only group, without changing style
+        assert_eq!(runs("{ab}c"), vec![("abc".to_string(), RunStyle::normal())]);
+    }
+
+    #[test]
+    fn invalid_markup_is_error() {
+        assert!(matches!(parse("x^{2"), Err(MarkupError::Unclosed)));
+        assert!(matches!(parse("{x"), Err(MarkupError::Unclosed)));
+        assert!(matches!(parse("x}"), Err(MarkupError::Unmatched)));
+        assert!(matches!(parse("x^"), Err(MarkupError::MissingScript)));
+        assert!(matches!(parse("x_"), Err(MarkupError::MissingScript)));
+        assert!(matches!(parse("\\foo"), Err(MarkupError::Command(name)) if name == "foo"));
+        assert!(matches!(parse("x\\"), Err(MarkupError::Command(name)) if name.is_empty()));
+    }
+
+    #[test]
+    fn escaped_text_parses_as_written() {
+        for text in ["a_b^c{d}\\e", "}{", "^", "1.5e-3", ""] {
+            let parsed: String = parse(&escape(text))
+                .unwrap()
+                .into_iter()
+                .map(|run| run.text)
+                .collect();
+            assert_eq!(parsed, text);
+        }
+        assert_eq!(escape("x = 1.0"), "x = 1.0");
+    }
+}
@@ -0,0 +1,109 @@
+extern crate gl;
+extern crate glam;
+use crate::gl_wrap::{Buffer, Program, VertexArray};
+use crate::plot::{Bounds, DataRange};
+use crate::scene::{Scene, SceneBuilder};
+use crate::text::{FontMap, TextBatch, TextParams};
+use crate::vertices::PosVert;
+use glam::Vec3;
+
+// segment between two data points, in data units
+pub struct Measurement {
+    pub a: [f32; 3],
+    pub b: [f32; 3],
+}
+
+impl Measurement {
+    pub fn new(a: [f32; 3], b: [f32; 3]) -> Self {
+        Self { a, b }
+    }
+
+    pub fn delta(&self) -> Vec3 {
+        Vec3::from(self.b) - Vec3::from(self.a)
+    }
+
+    pub fn distance(&self) -> f32 {
+        self.delta().length()
+    }
+
+    // segment line with label at midpoint showing distance and per axis deltas
+    pub fn get_scene(
+        &self,
+        mvp: [f32; 16],
+        range: &DataRange,
+        bounds: &Bounds,
+        param: &TextParams,
+        font: &FontMap,
+    ) -> Result<Scene, MeasureError> {
+        let a = range.to_box(Vec3::from(self.a), bounds);
+        let b = range.to_box(Vec3::from(self.b), bounds);
+        let line_verts = vec![
+            PosVert {
+                position: a.to_array(),
+            },
+            PosVert {
+                position: b.to_array(),
+            },
+        ];
+        let d = self.delta();
+        let label = format!(
+            "d = {:.3}\n\\Delta x = {:.3}\n\\Delta y = {:.3}\n\\Delta z = {:.3}",
+            self.distance(),
+            d.x,
+            d.y,
+            d.z
+        );
+        let midpoint = ((a + b) * 0.5).to_array();
+        let mut labels = TextBatch::new();
+        labels.group(font, param, None);
+        labels.add(&label, midpoint)?;
+
+        let mut scene = SceneBuilder::new();
+        const LINE_VERT: &str = "./shaders/solid_vert.glsl";
+        const LINE_FRAG: &str = "./shaders/solid_frag.glsl";
+        let line_program = scene.add_program(Program::new_from_files(LINE_VERT, LINE_FRAG)?);
+        let line_pos_loc = scene
+            .program(line_program)
+            .get_attrib_location("position")?;
+        let line_vao = VertexArray::new();
+        scene.add_buffer(Buffer::new_from(&line_verts, gl::STATIC_DRAW));
+        line_vao.set_attribute::<PosVert>(line_pos_loc, 3, 0);
+        let line_vao = scene.add_vao(line_vao, line_program);
+        let line_mvp = scene.add_uniform(line_program, "mvp", &mvp)?;
+        let line_color = scene.add_uniform(line_program, "color", &LINE_COLOR)?;
+        // segment, followed by label background, border and text
+        scene.add_pass(
+            gl::LINES,
+            (0, line_verts.len()),
+            line_program,
+            line_vao,
+            None,
+            &[line_mvp, line_color],
+        );
+        labels.add_to_scene(&mut scene, mvp)?;
+        Ok(scene.build()?)
+    }
+}
+
+static LINE_COLOR: [f32; 4] = [1.0, 0.4, 0.3, 1.0];
+
+extern crate thiserror;
+use crate::gl_wrap::{ProgramError, ShaderError, UniformError};
+use crate::scene::SceneError;
+use crate::text::{FontMapError, TextSceneError};
+use thiserror::Error;
+#[derive(Error, Debug)]
+pub enum MeasureError {
+    #[error("{0}")]
+    Shader(#[from] ShaderError),
+    #[error("{0}")]
+    Program(#[from] ProgramError),
+    #[error("{0}")]
+    Uniform(#[from] UniformError),
+    #[error("{0}")]
+    FontMap(#[from] FontMapError),
+    #[error("{0}")]
+    Scene(#[from] SceneError),
+    #[error("{0}")]
+    Text(#[from] TextSceneError),
+}
@@ -1,71 +1,645 @@
 extern crate gl;
 extern crate glam;
-use crate::axis::Axis;
-use crate::gl_wrap::Window;
-use crate::text::FontMapper;
+use crate::axis::{Axis, AxisPlacement};
+use crate::camera::{Camera, CameraState, Projection, View};
+use crate::figure::PlotWindows;
+use crate::gl_wrap::{Renderer, Window};
+use crate::measure::Measurement;
+use crate::scene::Scene;
+use crate::select::{SelectMode, Selection, SelectionScene};
+use crate::series::{self, Pick, Series, SeriesScene};
+use crate::stream::{SeriesData, SeriesHandle, SeriesUpdate};
+use crate::text::{FontMap, FontMapper, HAlign, TextAnchor, TextBox, TextParams, VAlign};
 use crate::ticks::Ticks;
-use glam::{Mat4, Vec3};
+use glam::Vec3;
+use glutin::event::{ElementState, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent};
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender};
 
 pub struct Plot {
-    window: Window,
-    mvp: [f32; 16],
+    size: [f32; 2],
+    camera: Camera,
     bg_color: [f32; 3],
+    range: DataRange,
+    aspect: Aspect,
+    auto_range: bool,
     bounds: Bounds,
     font_mapper: FontMapper,
+    window: Option<Window>, // none for plots drawn in figure cells
+    series: Vec<Series>,
+    sender: Sender<SeriesUpdate>,
+    receiver: Receiver<SeriesUpdate>,
+    on_select: Option<SelectCallback>,
+    on_camera_save: Option<CameraCallback>,
     pub axis: Axis,
     pub ticks: Ticks,
+    pub tooltip: TextParams,
 }
 
+// called with indices of selected points for each series, in order added
+pub type SelectCallback = Box<dyn FnMut(&[Vec<usize>])>;
+
+// called with current view when C is pressed, e.g. to save it for Plot::set_camera
+pub type CameraCallback = Box<dyn FnMut(&CameraState)>;
+
 impl Plot {
     pub fn new(title: &str, width: f64, height: f64) -> Result<Self, PlotError> {
-        let proj_matrix = Mat4::perspective_rh_gl(
-            DEFAULT_FOV,
-            (width / height) as f32,
-            CAMERA_NEAR,
-            CAMERA_FAR,
-        );
-        let view_matrix = Mat4::look_at_rh(DEFAULT_EYE, Vec3::ZERO, Vec3::Y);
-        let mvp = proj_matrix.mul_mat4(&view_matrix).to_cols_array();
+        let window = Window::new(title, width, height)?;
+        let mut plot = Self::new_cell([width, height], [width, height])?;
+        plot.window = Some(window);
+        Ok(plot)
+    }
+
+    // plot without own window, drawn in figure cell of given size
+    // figure window context must be current for font mapper creation
+    pub(crate) fn new_cell(size: [f64; 2], window_size: [f64; 2]) -> Result<Self, PlotError> {
+        let [width, height] = size;
+        let range = DataRange::new([0.0, 1.0], [0.0, 1.0], [0.0, 1.0]);
+        let aspect = Aspect::Equal;
+        let bounds = aspect.get_bounds(&range);
+        let mut camera = Camera::new((width / height) as f32);
+        camera.fit(bounds.extents());
+        let (sender, receiver) = mpsc::channel();
         Ok(Self {
-            window: Window::new(title, width, height)?,
-            mvp,
+            window: None,
+            size: [width as f32, height as f32],
+            camera,
             bg_color: DEFAULT_BG,
-            bounds: Bounds::new(1.0, 1.0, 1.0),
-            font_mapper: FontMapper::new(width as i32, height as i32)?,
+            range,
+            aspect,
+            auto_range: false,
+            bounds,
+            font_mapper: FontMapper::new(
+                window_size[0] as i32,
+                window_size[1] as i32,
+                height as i32,
+            )?,
+            series: Vec::new(),
+            sender,
+            receiver,
+            on_select: None,
+            on_camera_save: None,
             axis: Axis::new(),
             ticks: Ticks::new(),
+            tooltip: default_tooltip(),
         })
     }
 
+    // show plot, blocking until its window is closed
+    #[allow(dead_code)]
     pub fn display(self) -> Result<(), PlotError> {
-        let axis_font = self
-            .font_mapper
-            .gen_font_map(&self.axis.labels.param.font)?;
-        let ticks_font = self
-            .font_mapper
-            .gen_font_map(&self.ticks.labels.param.font)?;
-        let scenes = vec![
-            self.axis.get_scene(self.mvp, &self.bounds, &axis_font)?,
-            self.ticks.get_scene(self.mvp, &self.bounds, &ticks_font)?,
-        ];
-        unsafe {
-            gl::ClearColor(self.bg_color[0], self.bg_color[1], self.bg_color[2], 1.0);
+        PlotWindows::new(vec![self])?.run()
+    }
+
+    // show plot without blocking, window is updated on each PlotWindows::poll
+    #[allow(dead_code)]
+    pub fn show(self) -> Result<PlotWindows, PlotError> {
+        PlotWindows::new(vec![self])
+    }
+
+    pub(crate) fn size(&self) -> [f32; 2] {
+        self.size
+    }
+
+    pub(crate) fn take_window(&mut self) -> Option<Window> {
+        self.window.take()
+    }
+
+    // build gl resources in current context, returning state driven by event loop
+    pub(crate) fn into_view(mut self) -> Result<PlotView, PlotError> {
+        if self.auto_range {
+            if let Some(range) = DataRange::fit(&self.series) {
+                self.range = range;
+                self.update_bounds();
+            }
         }
-        self.window.run(scenes);
-        Ok(())
+        // generate single font map for each font used by labels
+        let mut fonts = HashMap::<String, FontMap>::new();
+        let mut font_files = vec![&self.axis.labels.param.font, &self.tooltip.font];
+        for axis in self.ticks.axes() {
+            font_files.push(&axis.param.font);
+        }
+        for file in font_files {
+            if !fonts.contains_key(file) {
+                fonts.insert(file.clone(), self.font_mapper.gen_font_map(file)?);
+            }
+        }
+        let mut view = PlotView {
+            size: self.size,
+            bg_color: self.bg_color,
+            placement: AxisPlacement::from_view(&self.bounds, &self.camera),
+            camera: self.camera,
+            bounds: self.bounds,
+            range: self.range,
+            aspect: self.aspect,
+            auto_range: self.auto_range,
+            data_changed: false,
+            selected: vec![vec![]; self.series.len()],
+            stale: vec![true; self.series.len()],
+            series: self.series,
+            receiver: self.receiver,
+            on_select: self.on_select,
+            on_camera_save: self.on_camera_save,
+            axis: self.axis,
+            ticks: self.ticks,
+            tooltip: self.tooltip,
+            fonts,
+            scenes: vec![],
+            series_scenes: vec![],
+            hover: None,
+            tooltip_scene: None,
+            tool: Tool::Rotate,
+            selection: None,
+            selection_scene: None,
+            measure_start: None,
+            measurement: None,
+            measure_scene: None,
+            measure_stale: false,
+            cursor: [0.0, 0.0],
+            dragging: false,
+        };
+        view.scenes = view.get_scenes()?;
+        Ok(view)
+    }
+
+    // add series, returning handle for updating its data from any thread once displayed
+    // name is shown on single tooltip line so may not contain control characters
+    pub fn add_series(&mut self, series: Series) -> Result<SeriesHandle, PlotError> {
+        if series.name.chars().any(char::is_control) {
+            return Err(PlotError::SeriesName(series.name));
+        }
+        self.series.push(series);
+        let index = self.series.len() - 1;
+        Ok(SeriesHandle::new(
+            index,
+            self.sender.clone(),
+            Window::proxy()?,
+        ))
+    }
+
+    // set function receiving points selected by box or lasso drag
+    pub fn on_select<F: FnMut(&[Vec<usize>]) + 'static>(&mut self, callback: F) {
+        self.on_select = Some(Box::new(callback));
+    }
+
+    // set function receiving camera state when C is pressed
+    pub fn on_camera_save<F: FnMut(&CameraState) + 'static>(&mut self, callback: F) {
+        self.on_camera_save = Some(Box::new(callback));
     }
 
     pub fn set_background_color(&mut self, color: [f32; 3]) {
         self.bg_color = color;
     }
 
-    pub fn set_bounds(&mut self, x: f32, y: f32, z: f32) {
-        self.bounds.x = x;
-        self.bounds.y = y;
-        self.bounds.z = z;
+    #[allow(dead_code)]
+    pub fn camera(&self) -> CameraState {
+        self.camera.state()
+    }
+
+    // restore saved view, error if state has no valid view
+    #[allow(dead_code)]
+    pub fn set_camera(&mut self, state: &CameraState) -> Result<(), PlotError> {
+        Ok(self.camera.set_state(state)?)
+    }
+
+    #[allow(dead_code)]
+    pub fn set_view(&mut self, view: View) {
+        self.camera.set_view(view);
+    }
+
+    #[allow(dead_code)]
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.camera.projection = projection;
+    }
+
+    // set data range of each axis starting from zero
+    pub fn set_bounds(&mut self, x: f32, y: f32, z: f32) -> Result<(), PlotError> {
+        self.set_range([0.0, x], [0.0, y], [0.0, z])
+    }
+
+    // set min and max data values shown along each axis, min must be below max
+    pub fn set_range(&mut self, x: [f32; 2], y: [f32; 2], z: [f32; 2]) -> Result<(), PlotError> {
+        for (axis, range) in ["x", "y", "z"].into_iter().zip([x, y, z]) {
+            check_range(axis, range)?;
+        }
+        self.range = DataRange::new(x, y, z);
+        self.update_bounds();
+        Ok(())
+    }
+
+    // ratio sides must be positive so box has nonzero size
+    pub fn set_aspect(&mut self, aspect: Aspect) -> Result<(), PlotError> {
+        if let Aspect::Ratio(ratio) = aspect {
+            if !ratio.iter().all(|r| r.is_normal() && *r > 0.0) {
+                return Err(PlotError::Aspect(ratio));
+            }
+        }
+        self.aspect = aspect;
+        self.update_bounds();
+        Ok(())
+    }
+
+    // fit range to data of all series on display and whenever streamed data changes
+    pub fn set_auto_range(&mut self, auto_range: bool) {
+        self.auto_range = auto_range;
+    }
+
+    // resize drawn box for current range and aspect, refitting camera to new box
+    // unless camera was set explicitly
+    fn update_bounds(&mut self) {
+        self.bounds = self.aspect.get_bounds(&self.range);
+        self.camera.fit(self.bounds.extents());
+    }
+}
+
+// plot state owned by window event loop once displayed
+pub(crate) struct PlotView {
+    size: [f32; 2],
+    bg_color: [f32; 3],
+    camera: Camera,
+    bounds: Bounds,
+    range: DataRange,
+    aspect: Aspect,
+    auto_range: bool,
+    data_changed: bool, // series data changed since range was last fit
+    series: Vec<Series>,
+    selected: Vec<Vec<usize>>,
+    stale: Vec<bool>, // series scenes needing full rebuild
+    receiver: Receiver<SeriesUpdate>,
+    on_select: Option<SelectCallback>,
+    on_camera_save: Option<CameraCallback>,
+    axis: Axis,
+    ticks: Ticks,
+    tooltip: TextParams,
+    fonts: HashMap<String, FontMap>,
+    placement: AxisPlacement,
+    scenes: Vec<Scene>,
+    series_scenes: Vec<SeriesScene>,
+    hover: Option<Pick>,
+    tooltip_scene: Option<(Pick, Scene)>, // scene built for currently shown pick
+    tool: Tool,
+    selection: Option<Selection>,
+    selection_scene: Option<SelectionScene>, // outline of selection being dragged
+    measure_start: Option<[f32; 3]>,         // first clicked point of next measurement
+    measurement: Option<Measurement>,
+    measure_scene: Option<Scene>,
+    measure_stale: bool,
+    cursor: [f64; 2], // logical px within cell, converted by figure view
+    dragging: bool,
+}
+
+impl PlotView {
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    // follow rotation of linked plot
+    pub fn link_camera(&mut self, state: &CameraState) {
+        self.camera.link_to(state);
+    }
+
+    fn get_scenes(&self) -> Result<Vec<Scene>, PlotError> {
+        let mvp = self.camera.mvp();
+        let axis_font = &self.fonts[&self.axis.labels.param.font];
+        Ok(vec![
+            self.axis
+                .get_scene(mvp, &self.bounds, &self.placement, axis_font)?,
+            self.ticks.get_scene(
+                mvp,
+                self.size,
+                &self.bounds,
+                &self.range,
+                &self.placement,
+                &self.fonts,
+            )?,
+        ])
+    }
+
+    // rebuild series scenes marked stale, otherwise write any appended points in place
+    fn update_series_scenes(&mut self) -> Result<(), PlotError> {
+        let mvp = self.camera.mvp();
+        for (i, series) in self.series.iter().enumerate() {
+            let (range, bounds) = (&self.range, &self.bounds);
+            if let Some(scene) = self.series_scenes.get_mut(i) {
+                if !self.stale[i] && scene.append(series) {
+                    scene.scene.set_uniform("mvp", &mvp)?;
+                    scene.set_range(range, bounds)?;
+                    continue;
+                }
+            }
+            let scene = series.get_scene(mvp, self.size, range, bounds, &self.selected[i])?;
+            match self.series_scenes.get_mut(i) {
+                Some(old) => *old = scene,
+                None => self.series_scenes.push(scene),
+            }
+            self.stale[i] = false;
+        }
+        Ok(())
+    }
+
+    // fit range to current series data, returning true if range changed
+    fn fit_range(&mut self) -> bool {
+        let range = match DataRange::fit(&self.series) {
+            Some(range) if range != self.range => range,
+            _ => return false,
+        };
+        self.range = range;
+        // box resized by aspect, e.g. equal aspect following data extents,
+        // refitting camera unless set explicitly
+        let bounds = self.aspect.get_bounds(&self.range);
+        if bounds.extents() != self.bounds.extents() {
+            self.camera.fit(bounds.extents());
+        }
+        self.bounds = bounds;
+        // labels anchored to data points moved within box
+        self.measure_stale = true;
+        self.tooltip_scene = None;
+        true
+    }
+
+    // rebuild measurement segment and label after measurement changes
+    fn update_measure_scene(&mut self) -> Result<(), PlotError> {
+        if self.measure_stale {
+            self.measure_stale = false;
+            self.measure_scene = None;
+            if let Some(measurement) = &self.measurement {
+                self.measure_scene = Some(measurement.get_scene(
+                    self.camera.mvp(),
+                    &self.range,
+                    &self.bounds,
+                    &self.tooltip,
+                    &self.fonts[&self.tooltip.font],
+                )?);
+            }
+        }
+        if let Some(scene) = &mut self.measure_scene {
+            scene.set_uniform("mvp", &self.camera.mvp())?;
+        }
+        Ok(())
+    }
+
+    // rebuild tooltip scene when hovered point changes
+    fn update_tooltip(&mut self) -> Result<(), PlotError> {
+        let shown = self.tooltip_scene.as_ref().map(|(pick, _)| *pick);
+        if shown == self.hover {
+            return Ok(());
+        }
+        self.tooltip_scene = None;
+        if let Some(pick) = self.hover {
+            let scene = self.series[pick.series].get_tooltip_scene(
+                pick.index,
+                self.camera.mvp(),
+                &self.range,
+                &self.bounds,
+                &self.tooltip,
+                &self.fonts[&self.tooltip.font],
+            )?;
+            self.tooltip_scene = Some((pick, scene));
+        }
+        Ok(())
+    }
+
+    // find points inside finished selection and pass them to user callback
+    fn finish_selection(&mut self, selection: Selection) {
+        self.selected = series::select(
+            &self.series,
+            &selection,
+            self.camera.mvp(),
+            self.size,
+            &self.range,
+            &self.bounds,
+        );
+        if let Some(callback) = &mut self.on_select {
+            callback(&self.selected);
+        }
+        self.stale.iter_mut().for_each(|s| *s = true);
+    }
+
+    // measure from previously clicked point to point under cursor,
+    // returning false if no point is under cursor
+    fn measure_click(&mut self) -> bool {
+        let Some(pick) = self.hover else {
+            return false;
+        };
+        let point = self.series[pick.series].points()[pick.index];
+        match self.measure_start.take() {
+            None => self.measure_start = Some(point),
+            Some(start) => {
+                self.measurement = Some(Measurement::new(start, point));
+                self.measure_stale = true;
+            }
+        }
+        true
+    }
+
+    // switch to tool, or back to rotating if already using it
+    fn toggle_tool(&mut self, tool: Tool) {
+        self.tool = match self.tool == tool {
+            true => Tool::Rotate,
+            false => tool,
+        };
+        self.measure_start = None;
+    }
+
+    // handle key press, returning true if view changed
+    fn handle_key(&mut self, key: VirtualKeyCode) -> bool {
+        match key {
+            VirtualKeyCode::B => {
+                self.toggle_tool(Tool::Select(SelectMode::Box));
+                return false;
+            }
+            VirtualKeyCode::L => {
+                self.toggle_tool(Tool::Select(SelectMode::Lasso));
+                return false;
+            }
+            VirtualKeyCode::M => {
+                self.toggle_tool(Tool::Measure);
+                return false;
+            }
+            VirtualKeyCode::Escape => {
+                // clear selection and measurement, returning to rotation
+                self.tool = Tool::Rotate;
+                self.selected.iter_mut().for_each(|s| s.clear());
+                self.stale.iter_mut().for_each(|s| *s = true);
+                self.measure_start = None;
+                self.measurement = None;
+                self.measure_stale = true;
+            }
+            VirtualKeyCode::P => self.camera.toggle_projection(),
+            VirtualKeyCode::C => {
+                if let Some(callback) = &mut self.on_camera_save {
+                    callback(&self.camera.state());
+                }
+                return false;
+            }
+            VirtualKeyCode::R | VirtualKeyCode::Key0 => self.camera.set_view(View::Default),
+            VirtualKeyCode::Key1 => self.camera.set_view(View::Top),
+            VirtualKeyCode::Key2 => self.camera.set_view(View::Front),
+            VirtualKeyCode::Key3 => self.camera.set_view(View::Side),
+            VirtualKeyCode::Key4 => self.camera.set_view(View::Isometric),
+            VirtualKeyCode::Left => self.camera.orbit(-KEY_ROTATE, 0.0),
+            VirtualKeyCode::Right => self.camera.orbit(KEY_ROTATE, 0.0),
+            VirtualKeyCode::Up => self.camera.orbit(0.0, -KEY_ROTATE),
+            VirtualKeyCode::Down => self.camera.orbit(0.0, KEY_ROTATE),
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl Renderer for PlotView {
+    type Error = PlotError;
+
+    fn handle_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => {
+                let pressed = *state == ElementState::Pressed;
+                let cursor = [self.cursor[0] as f32, self.cursor[1] as f32];
+                match (pressed, self.tool) {
+                    (true, Tool::Select(mode)) => {
+                        self.selection = Some(Selection::new(mode, cursor));
+                        self.selection_scene = None;
+                    }
+                    // rotate when measure click misses all points
+                    (true, Tool::Measure) if self.measure_click() => return true,
+                    (true, _) => self.dragging = true,
+                    (false, _) => {
+                        self.dragging = false;
+                        if let Some(selection) = self.selection.take() {
+                            self.finish_selection(selection);
+                            return true;
+                        }
+                    }
+                }
+                false
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let dx = (position.x - self.cursor[0]) as f32;
+                let dy = (position.y - self.cursor[1]) as f32;
+                self.cursor = [position.x, position.y];
+                if let Some(selection) = &mut self.selection {
+                    selection.drag_to([position.x as f32, position.y as f32]);
+                    return true;
+                }
+                if self.dragging {
+                    self.camera.orbit(dx * ROTATE_SPEED, -dy * ROTATE_SPEED);
+                    return true;
+                }
+                // redraw only when hovered point changes
+                let hover = series::pick(
+                    &self.series,
+                    self.camera.mvp(),
+                    self.size,
+                    [position.x as f32, position.y as f32],
+                    &self.range,
+                    &self.bounds,
+                );
+                let changed = hover != self.hover;
+                self.hover = hover;
+                changed
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(key),
+                        ..
+                    },
+                ..
+            } => self.handle_key(*key),
+            _ => false,
+        }
+    }
+
+    fn update(&mut self) -> bool {
+        let mut changed = false;
+        for update in self.receiver.try_iter() {
+            let s = update.series;
+            let shifted = match update.data {
+                SeriesData::Append(points) => self.series[s].append(points),
+                SeriesData::Replace(points) => {
+                    self.series[s].set_points(points);
+                    self.stale[s] = true;
+                    true
+                }
+            };
+            // indices of selected and hovered points no longer refer to same points
+            if shifted {
+                if !self.selected[s].is_empty() {
+                    self.selected[s].clear();
+                    self.stale[s] = true;
+                }
+                if self.hover.is_some_and(|pick| pick.series == s) {
+                    self.hover = None;
+                }
+            }
+            self.data_changed = true;
+            changed = true;
+        }
+        changed
+    }
+
+    fn draw(&mut self) -> Result<(), PlotError> {
+        // clear only plot viewport when scissor set by figure
+        unsafe {
+            gl::ClearColor(self.bg_color[0], self.bg_color[1], self.bg_color[2], 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
+        let range_changed =
+            self.auto_range && std::mem::take(&mut self.data_changed) && self.fit_range();
+        // move ticks and labels to visible box edges when view changes
+        let placement = AxisPlacement::from_view(&self.bounds, &self.camera);
+        if placement != self.placement || range_changed {
+            self.placement = placement;
+            self.scenes = self.get_scenes()?;
+        } else {
+            let mvp = self.camera.mvp();
+            for scene in &mut self.scenes {
+                scene.set_uniform("mvp", &mvp)?;
+            }
+        }
+        self.update_series_scenes()?;
+        self.update_measure_scene()?;
+        self.update_tooltip()?;
+        if let Some((_, scene)) = &mut self.tooltip_scene {
+            scene.set_uniform("mvp", &self.camera.mvp())?;
+        }
+        for scene in &self.scenes {
+            scene.draw();
+        }
+        for series_scene in &self.series_scenes {
+            series_scene.scene.draw();
+        }
+        if let Some(scene) = &self.measure_scene {
+            scene.draw();
+        }
+        // tooltip and selection outline drawn last to stay on top of plot
+        if let Some((_, scene)) = &self.tooltip_scene {
+            scene.draw();
+        }
+        // outline built once per drag and extended in place, freed once drag ends
+        match &self.selection {
+            Some(selection) => {
+                let scene = self.selection_scene.as_mut();
+                let written = scene.is_some_and(|s| s.update(selection));
+                if !written {
+                    self.selection_scene = Some(selection.get_scene(self.size)?);
+                }
+            }
+            None => self.selection_scene = None,
+        }
+        if let Some(selection_scene) = &self.selection_scene {
+            selection_scene.scene.draw();
+        }
+        Ok(())
     }
 }
 
+// size of drawn axes box, spanning origin to (x, y, z)
 pub struct Bounds {
     pub x: f32,
     pub y: f32,
@@ -77,29 +651,139 @@ impl Bounds {
         Self { x, y, z }
     }
 
-    pub fn max(&self) -> f32 {
-        self.x.max(self.y).max(self.x)
+    pub fn extents(&self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+}
+
+// min and max data values shown along each axis
+#[derive(PartialEq)]
+pub struct DataRange {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl DataRange {
+    pub fn new(x: [f32; 2], y: [f32; 2], z: [f32; 2]) -> Self {
+        Self {
+            min: Vec3::new(x[0], y[0], z[0]),
+            max: Vec3::new(x[1], y[1], z[1]),
+        }
+    }
+
+    // smallest range containing points of all series with margin, none if no points
+    pub fn fit(series: &[Series]) -> Option<Self> {
+        Self::fit_points(
+            series
+                .iter()
+                .flat_map(|s| s.points())
+                .map(|&p| Vec3::from(p)),
+        )
+    }
+
+    // smallest range containing points with margin, none if no points
+    pub fn fit_points(mut points: impl Iterator<Item = Vec3>) -> Option<Self> {
+        let first = points.next()?;
+        let (min, max) = points.fold((first, first), |(min, max), p| (min.min(p), max.max(p)));
+        // pad flat axes to unit range so box has nonzero size
+        let size = max - min;
+        let pad = Vec3::select(size.cmpgt(Vec3::ZERO), size * FIT_PAD, Vec3::splat(0.5));
+        Some(Self {
+            min: min - pad,
+            max: max + pad,
+        })
+    }
+
+    pub fn size(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    // drawn box length per data unit along each axis
+    pub fn box_scale(&self, bounds: &Bounds) -> Vec3 {
+        bounds.extents() / self.size()
+    }
+
+    // convert point in data units to position in drawn box
+    pub fn to_box(&self, point: Vec3, bounds: &Bounds) -> Vec3 {
+        (point - self.min) * self.box_scale(bounds)
+    }
+
+    // convert value along axis a in data units to position on box edge
+    pub fn to_box_axis(&self, a: usize, value: f32, bounds: &Bounds) -> f32 {
+        (value - self.min[a]) / self.size()[a] * bounds.extents()[a]
     }
 }
 
-pub static DEFAULT_EYE: Vec3 = Vec3::new(2.0, 2.0, 2.0);
-static DEFAULT_FOV: f32 = 50.0 * std::f32::consts::PI / 180.0;
-static CAMERA_NEAR: f32 = 0.0;
-static CAMERA_FAR: f32 = 10.0;
-static DEFAULT_BG: [f32; 3] = [0.1, 0.1, 0.1];
+// relation between data range and drawn box size
+#[allow(dead_code)]
+pub enum Aspect {
+    Equal,           // one data unit drawn same length on all axes
+    Cube,            // all axes drawn same length regardless of range
+    Ratio([f32; 3]), // explicit relative box side lengths
+}
+
+impl Aspect {
+    // get box size with longest side of unit length
+    pub fn get_bounds(&self, range: &DataRange) -> Bounds {
+        let sides = match self {
+            Aspect::Equal => range.size(),
+            Aspect::Cube => Vec3::ONE,
+            Aspect::Ratio(ratio) => Vec3::from(*ratio),
+        };
+        let sides = sides / sides.max_element();
+        Bounds::new(sides.x, sides.y, sides.z)
+    }
+}
+
+// check axis range spans nonzero finite extent, so data maps to box without dividing by zero
+pub(crate) fn check_range(axis: &'static str, [min, max]: [f32; 2]) -> Result<(), PlotError> {
+    match min < max && (max - min).is_normal() {
+        true => Ok(()),
+        false => Err(PlotError::Range { axis, min, max }),
+    }
+}
+
+// action of left mouse drag or click
+#[derive(Copy, Clone, PartialEq)]
+enum Tool {
+    Rotate,
+    Select(SelectMode),
+    Measure, // click two points to measure between them
+}
+
+// label box shown next to hovered data point
+fn default_tooltip() -> TextParams {
+    let mut param = TextParams::new(12.0);
+    param.anchor = TextAnchor::new(HAlign::Left, VAlign::Bottom);
+    param.background = Some(TextBox {
+        fill: [0.0, 0.0, 0.0, 0.8],
+        border: Some([0.6, 0.6, 0.6, 1.0]),
+        padding: 4.0,
+    });
+    param
+}
+
+static ROTATE_SPEED: f32 = 0.01; // radians per pixel of mouse drag
+static KEY_ROTATE: f32 = 5.0 * std::f32::consts::PI / 180.0; // radians per arrow key press
+static FIT_PAD: f32 = 0.05; // auto range margin as fraction of data extent
+pub(crate) static DEFAULT_BG: [f32; 3] = [0.1, 0.1, 0.1];
 
 extern crate thiserror;
 use thiserror::Error;
 extern crate glutin;
 use crate::axis::AxisError;
-use crate::gl_wrap::ShaderError;
+use crate::camera::CameraError;
+use crate::gl_wrap::{ShaderError, UniformError, WindowError};
+use crate::measure::MeasureError;
+use crate::plot2d::Plot2dError;
+use crate::select::SelectError;
+use crate::series::SeriesError;
 use crate::text::FontMapperError;
 use crate::ticks::TicksError;
-use glutin::CreationError;
 #[derive(Error, Debug)]
 pub enum PlotError {
     #[error("{0}")]
-    Creation(#[from] CreationError),
+    Window(#[from] WindowError),
     #[error("{0}")]
     Shader(#[from] ShaderError),
     #[error("{0}")]
@@ -108,4 +792,28 @@ pub enum PlotError {
     Ticks(#[from] TicksError),
     #[error("{0}")]
     Font(#[from] FontMapperError),
+    #[error("{0}")]
+    Uniform(#[from] UniformError),
+    #[error("{0}")]
+    Series(#[from] SeriesError),
+    #[error("{0}")]
+    Select(#[from] SelectError),
+    #[error("{0}")]
+    Measure(#[from] MeasureError),
+    #[error("{0}")]
+    Plot2d(#[from] Plot2dError),
+    #[error("Series name {0:?} contains control characters")]
+    SeriesName(String),
+    #[error("Invalid {axis} range [{min}, {max}], min must be below max")]
+    Range {
+        axis: &'static str,
+        min: f32,
+        max: f32,
+    },
+    #[error("Aspect ratio {0:?} must have positive sides")]
+    Aspect([f32; 3]),
+    #[error("{0}")]
+    Camera(#[from] CameraError),
+    #[error("Figure cell {0:?} outside grid of {1:?} rows and columns")]
+    Cell([usize; 2], [usize; 2]),
 }
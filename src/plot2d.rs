@@ -0,0 +1,450 @@
+extern crate gl;
+extern crate glam;
+use crate::axis::EdgePlacement;
+use crate::gl_wrap::{Bind, Buffer, Program, VertexArray};
+use crate::plot::{check_range, DataRange, PlotError, DEFAULT_BG};
+use crate::scene::{Scene, SceneBuilder};
+use crate::text::{FontMap, FontMapper, HAlign, TextAnchor, TextBatch, TextParams, VAlign};
+use crate::ticks::{AxisTicks, TickStyle};
+use crate::vertices::{quad_inds, quad_range, PosVert, TickVert, MARKER_QUAD};
+use glam::{vec3, Mat4, Vec3};
+use std::collections::HashMap;
+
+// 2d line or scatter plot drawn in screen space, e.g. beside 3d plot in figure
+pub struct Plot2d {
+    size: [f32; 2],
+    font_mapper: FontMapper,
+    lines: Vec<Line2d>,
+    range: Option<[[f32; 2]; 2]>, // x and y min, max, fit to data if none
+    pub bg_color: [f32; 3],
+    pub color: [f32; 4], // frame color
+    pub x_label: String,
+    pub y_label: String,
+    pub x_ticks: AxisTicks,
+    pub y_ticks: AxisTicks,
+    pub label_param: TextParams,
+}
+
+// named sequence of 2d data points, listed in legend unless name is empty
+pub struct Line2d {
+    pub name: String,
+    pub points: Vec<[f32; 2]>,
+    pub color: [f32; 4],
+    pub style: LineStyle,
+    pub size: f32, // marker diameter in px for scatter style
+}
+
+#[derive(PartialEq)]
+pub enum LineStyle {
+    Line,
+    Scatter,
+}
+
+impl Line2d {
+    pub fn new(name: &str, points: Vec<[f32; 2]>) -> Self {
+        Self {
+            name: name.to_string(),
+            points,
+            color: [0.2, 0.6, 1.0, 1.0],
+            style: LineStyle::Line,
+            size: 4.0,
+        }
+    }
+}
+
+impl Plot2d {
+    // plot in figure cell of given size, figure window context must be current
+    pub(crate) fn new_cell(size: [f64; 2], window_size: [f64; 2]) -> Result<Self, PlotError> {
+        let mut x_ticks = AxisTicks::new();
+        x_ticks.param.anchor = TextAnchor::new(HAlign::Center, VAlign::Top);
+        let mut y_ticks = AxisTicks::new();
+        y_ticks.param.anchor = TextAnchor::new(HAlign::Right, VAlign::Middle);
+        let mut label_param = TextParams::new(12.0);
+        label_param.anchor = TextAnchor::new(HAlign::Center, VAlign::Top);
+        Ok(Self {
+            size: [size[0] as f32, size[1] as f32],
+            font_mapper: FontMapper::new(
+                window_size[0] as i32,
+                window_size[1] as i32,
+                size[1] as i32,
+            )?,
+            lines: vec![],
+            range: None,
+            bg_color: DEFAULT_BG,
+            color: [1.0, 1.0, 1.0, 1.0],
+            x_label: String::new(),
+            y_label: String::new(),
+            x_ticks,
+            y_ticks,
+            label_param,
+        })
+    }
+
+    pub fn add_line(&mut self, line: Line2d) {
+        self.lines.push(line);
+    }
+
+    // set min and max data values shown along each axis, instead of fitting to data
+    #[allow(dead_code)]
+    pub fn set_range(&mut self, x: [f32; 2], y: [f32; 2]) -> Result<(), PlotError> {
+        check_range("x", x)?;
+        check_range("y", y)?;
+        self.range = Some([x, y]);
+        Ok(())
+    }
+
+    // smallest range containing all lines with margin, unit range if no points
+    fn fit_range(&self) -> [[f32; 2]; 2] {
+        let points = self.lines.iter().flat_map(|l| &l.points);
+        match DataRange::fit_points(points.map(|p| vec3(p[0], p[1], 0.0))) {
+            Some(DataRange { min, max }) => [[min.x, max.x], [min.y, max.y]],
+            None => [[0.0, 1.0], [0.0, 1.0]],
+        }
+    }
+
+    // build gl resources in current context, returning state driven by event loop
+    pub(crate) fn into_view(self) -> Result<Plot2dView, PlotError> {
+        let mut fonts = HashMap::<String, FontMap>::new();
+        let font_files = [
+            &self.x_ticks.param.font,
+            &self.y_ticks.param.font,
+            &self.label_param.font,
+        ];
+        for file in font_files {
+            if !fonts.contains_key(file) {
+                fonts.insert(file.clone(), self.font_mapper.gen_font_map(file)?);
+            }
+        }
+        let scene = self.get_scene(&fonts)?;
+        // scene keeps font textures alive after font maps are dropped
+        Ok(Plot2dView {
+            bg_color: self.bg_color,
+            scene,
+        })
+    }
+
+    fn get_scene(&self, fonts: &HashMap<String, FontMap>) -> Result<Scene, Plot2dError> {
+        let [[x0, x1], [y0, y1]] = self.range.unwrap_or_else(|| self.fit_range());
+        let [w, h] = self.size;
+        let [ml, mr, mb, mt] = MARGIN;
+        // data units per px within plot area, for offsetting labels by px
+        let px = [(x1 - x0) / (w - ml - mr), (y1 - y0) / (h - mb - mt)];
+
+        // map data range to plot area inside margins in clip space
+        let (l, r) = (-1.0 + 2.0 * ml / w, 1.0 - 2.0 * mr / w);
+        let (b, t) = (-1.0 + 2.0 * mb / h, 1.0 - 2.0 * mt / h);
+        let (sx, sy) = ((r - l) / (x1 - x0), (t - b) / (y1 - y0));
+        let translation = vec3(l - x0 * sx, b - y0 * sy, 0.0);
+        let mvp = Mat4::from_translation(translation) * Mat4::from_scale(vec3(sx, sy, 1.0));
+        let mvp = mvp.to_cols_array();
+
+        // frame followed by grid lines and data line strips
+        let mut line_verts = vec![vert(x0, y0), vert(x1, y0), vert(x1, y1), vert(x0, y1)];
+        let mut grid_ranges = Vec::new();
+        let mut tick_verts = Vec::<TickVert>::new();
+        let mut tick_ranges = Vec::new();
+        let mut labels = TextBatch::new();
+        let axes = [(&self.x_ticks, 0), (&self.y_ticks, 1)];
+        for (axis, a) in axes {
+            let (min, max) = match a {
+                0 => (x0, x1),
+                _ => (y0, y1),
+            };
+            let (major, minor) = axis.locator().values(min, max, axis.minor_divisions);
+            // bottom or left edge of frame, with outward direction one px long
+            // so points on edge are offset by px
+            let edge = match a {
+                0 => EdgePlacement {
+                    origin: vec3(0.0, y0, 0.0),
+                    dir: Vec3::X,
+                    out: vec3(0.0, -px[1], 0.0),
+                },
+                _ => EdgePlacement {
+                    origin: vec3(x0, 0.0, 0.0),
+                    dir: Vec3::Y,
+                    out: vec3(-px[0], 0.0, 0.0),
+                },
+            };
+
+            let start = line_verts.len();
+            if let TickStyle::Grid = axis.style {
+                for &v in major.iter().chain(&minor) {
+                    line_verts.push(vert_on(a, v, [x0, y0]));
+                    line_verts.push(vert_on(a, v, [x1, y1]));
+                }
+            }
+            grid_ranges.push((start, line_verts.len() - start));
+
+            // major followed by minor tick marks
+            for (values, marks) in [(&major, &axis.major), (&minor, &axis.minor)] {
+                let start = tick_verts.len();
+                if let TickStyle::Tick = axis.style {
+                    tick_verts.append(&mut axis.get_tick_verts(values, &edge, marks));
+                }
+                tick_ranges.push((start, tick_verts.len() - start));
+            }
+
+            labels.group(get_font(fonts, &axis.param.font)?, &axis.param, None);
+            if axis.labels {
+                for &v in &major {
+                    let pos = edge.point(v, axis.major.length + LABEL_GAP);
+                    labels.add(&axis.formatter.format(v), pos)?;
+                }
+            }
+        }
+
+        // axis titles below x tick labels and along left edge of cell,
+        // followed by names of legend entries
+        let font = get_font(fonts, &self.label_param.font)?;
+        labels.group(font, &self.label_param, None);
+        let x_pos = [(x0 + x1) * 0.5, y0 - TITLE_GAP * px[1], 0.0];
+        labels.add(&self.x_label, x_pos)?;
+        let mut y_param = self.label_param.clone();
+        y_param.anchor = TextAnchor::new(HAlign::Center, VAlign::Top);
+        let y_pos = [x0 - (ml - LABEL_GAP) * px[0], (y0 + y1) * 0.5, 0.0];
+        let mut y_verts = font.get_verts(&self.y_label, &y_param, y_pos)?;
+        // turn y title a quarter counterclockwise in px to read upwards,
+        // offsets being scaled separately per axis in clip space
+        for vert in &mut y_verts {
+            let [x, y] = vert.offset;
+            vert.offset = [-y * h / w, x * w / h];
+        }
+        labels.add_verts(y_verts);
+        let mut legend_param = self.label_param.clone();
+        legend_param.anchor = TextAnchor::new(HAlign::Left, VAlign::Middle);
+        let mut legend_samples = Vec::new();
+        let named = self.lines.iter().filter(|line| !line.name.is_empty());
+        for (i, line) in named.enumerate() {
+            // point on row of entry, px right of top left corner of plot area
+            let row = LEGEND_PAD + (i as f32 + 0.5) * LEGEND_ROW;
+            let at = |x: f32| vert(x0 + (LEGEND_PAD + x) * px[0], y1 - row * px[1]);
+            let pos = at(LEGEND_SAMPLE + LABEL_GAP).position;
+            let name = font.literal(&line.name);
+            labels.add_verts(font.get_verts(&name, &legend_param, pos)?);
+            // short line segment or single marker in line style
+            let sample = match line.style {
+                LineStyle::Line => vec![at(0.0), at(LEGEND_SAMPLE)],
+                LineStyle::Scatter => vec![at(LEGEND_SAMPLE * 0.5)],
+            };
+            legend_samples.push((line, sample));
+        }
+
+        // data lines and scatter markers followed by legend samples,
+        // with range in line or point buffer per line
+        let mut point_verts = Vec::<PosVert>::new();
+        let mut data_ranges = Vec::new();
+        let data = self
+            .lines
+            .iter()
+            .map(|line| (line, line.points.iter().map(|p| vert(p[0], p[1])).collect()));
+        for (line, verts) in data.chain(legend_samples) {
+            let buffer = match line.style {
+                LineStyle::Line => &mut line_verts,
+                LineStyle::Scatter => &mut point_verts,
+            };
+            let start = buffer.len();
+            buffer.extend(verts);
+            data_ranges.push((line, (start, buffer.len() - start)));
+        }
+
+        let mut scene = SceneBuilder::new();
+        const LINE_VERT: &str = "./shaders/solid_vert.glsl";
+        const LINE_FRAG: &str = "./shaders/solid_frag.glsl";
+        let line_program = scene.add_program(Program::new_from_files(LINE_VERT, LINE_FRAG)?);
+        let line_pos_loc = scene
+            .program(line_program)
+            .get_attrib_location("position")?;
+        let line_vao = VertexArray::new();
+        scene.add_buffer(Buffer::new_from(&line_verts, gl::STATIC_DRAW));
+        line_vao.set_attribute::<PosVert>(line_pos_loc, 3, 0);
+        let line_vao = scene.add_vao(line_vao, line_program);
+
+        // tick marks extruded in screen space to fixed px length
+        const TICK_VERT: &str = "./shaders/tick_vert.glsl";
+        let tick_program = scene.add_program(Program::new_from_files(TICK_VERT, LINE_FRAG)?);
+        let program = scene.program(tick_program);
+        let tick_pos_loc = program.get_attrib_location("position")?;
+        let tick_dir_loc = program.get_attrib_location("direction")?;
+        let tick_off_loc = program.get_attrib_location("offset")?;
+        let tick_vao = VertexArray::new();
+        scene.add_buffer(Buffer::new_from(&tick_verts, gl::STATIC_DRAW));
+        tick_vao.set_attribute::<TickVert>(tick_pos_loc, 3, 0);
+        tick_vao.set_attribute::<TickVert>(tick_dir_loc, 3, 3);
+        tick_vao.set_attribute::<TickVert>(tick_off_loc, 1, 6);
+        let tick_vao = scene.add_vao(tick_vao, tick_program);
+
+        // scatter markers drawn as one quad instanced per point, centered in data units
+        const MARKER_VERT: &str = "./shaders/marker_vert.glsl";
+        const MARKER_FRAG: &str = "./shaders/marker_frag.glsl";
+        let marker_program = scene.add_program(Program::new_from_files(MARKER_VERT, MARKER_FRAG)?);
+        let program = scene.program(marker_program);
+        let marker_cor_loc = program.get_attrib_location("corner")?;
+        let marker_pos_loc = program.get_attrib_location("position")?;
+        let quad = scene.add_buffer(Buffer::new_from(&MARKER_QUAD, gl::STATIC_DRAW));
+        let quad_elements = scene.add_buffer(Buffer::new_from(&quad_inds(1), gl::STATIC_DRAW));
+        let points = scene.add_buffer(Buffer::new_from(&point_verts, gl::STATIC_DRAW));
+
+        let line_mvp = scene.add_uniform(line_program, "mvp", &mvp)?;
+        let frame_color = scene.add_uniform(line_program, "color", &self.color)?;
+        let tick_mvp = scene.add_uniform(tick_program, "mvp", &mvp)?;
+        let tick_viewport = scene.add_uniform(tick_program, "viewport", &self.size)?;
+        let marker_shared = [
+            scene.add_uniform(marker_program, "mvp", &mvp)?,
+            scene.add_uniform(marker_program, "viewport", &self.size)?,
+            scene.add_uniform(marker_program, "range_min", &[0.0; 3])?,
+            scene.add_uniform(marker_program, "range_scale", &[1.0; 3])?,
+        ];
+        let lines = (line_program, line_vao);
+        let ticks = (tick_program, tick_vao);
+        let mut passes = vec![(
+            gl::LINE_LOOP,
+            (0, 4),
+            lines,
+            None,
+            vec![line_mvp, frame_color],
+        )];
+
+        // per axis grid, tick marks and tick labels
+        for (a, axis) in [&self.x_ticks, &self.y_ticks].into_iter().enumerate() {
+            let grid_color = scene.add_uniform(line_program, "color", &axis.major.color)?;
+            let major_color = scene.add_uniform(tick_program, "color", &axis.major.color)?;
+            let minor_color = scene.add_uniform(tick_program, "color", &axis.minor.color)?;
+            let (major, minor) = (tick_ranges[2 * a], tick_ranges[2 * a + 1]);
+            passes.append(&mut vec![
+                (
+                    gl::LINES,
+                    grid_ranges[a],
+                    lines,
+                    None,
+                    vec![line_mvp, grid_color],
+                ),
+                (
+                    gl::LINES,
+                    minor,
+                    ticks,
+                    None,
+                    vec![tick_mvp, tick_viewport, minor_color],
+                ),
+                (
+                    gl::LINES,
+                    major,
+                    ticks,
+                    None,
+                    vec![tick_mvp, tick_viewport, major_color],
+                ),
+            ]);
+        }
+        for (draw_type, range, (program, vao), texture, uniform) in passes {
+            scene.add_pass(draw_type, range, program, vao, texture, &uniform);
+        }
+        // tick labels, titles and legend names with their backgrounds
+        labels.add_to_scene(&mut scene, mvp)?;
+        for (line, (start, count)) in data_ranges {
+            match line.style {
+                LineStyle::Line => {
+                    let color = scene.add_uniform(line_program, "color", &line.color)?;
+                    let uniform = [line_mvp, color];
+                    let range = (start, count);
+                    scene.add_pass(
+                        gl::LINE_STRIP,
+                        range,
+                        line_program,
+                        line_vao,
+                        None,
+                        &uniform,
+                    );
+                }
+                LineStyle::Scatter => {
+                    // vao pairing shared quad with this line's points, one instance each
+                    let mut vao = VertexArray::new();
+                    scene.buffer(quad).bind();
+                    vao.set_attribute::<[f32; 2]>(marker_cor_loc, 2, 0);
+                    scene.buffer(points).bind();
+                    let offset = (start * 3) as i32;
+                    vao.set_instance_attribute::<PosVert>(marker_pos_loc, 3, offset);
+                    vao.set_elements(scene.buffer(quad_elements));
+                    let vao = scene.add_vao(vao, marker_program);
+                    let color = scene.add_uniform(marker_program, "color", &line.color)?;
+                    let size = scene.add_uniform(marker_program, "size", &[line.size])?;
+                    let uniform = [marker_shared.as_slice(), &[color, size]].concat();
+                    let range = quad_range((0, MARKER_QUAD.len()));
+                    scene
+                        .add_pass(gl::TRIANGLES, range, marker_program, vao, None, &uniform)
+                        .indexed()
+                        .instanced(count);
+                }
+            }
+        }
+        Ok(scene.build()?)
+    }
+}
+
+// 2d plot state owned by window event loop once displayed
+pub(crate) struct Plot2dView {
+    bg_color: [f32; 3],
+    scene: Scene,
+}
+
+impl Plot2dView {
+    pub fn draw(&self) -> Result<(), PlotError> {
+        unsafe {
+            gl::ClearColor(self.bg_color[0], self.bg_color[1], self.bg_color[2], 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
+        self.scene.draw();
+        Ok(())
+    }
+}
+
+fn vert(x: f32, y: f32) -> PosVert {
+    PosVert {
+        position: [x, y, 0.0],
+    }
+}
+
+// point at value along axis a, with other coordinate taken from corner
+fn vert_on(a: usize, v: f32, corner: [f32; 2]) -> PosVert {
+    match a {
+        0 => vert(v, corner[1]),
+        _ => vert(corner[0], v),
+    }
+}
+
+fn get_font<'a>(
+    fonts: &'a HashMap<String, FontMap>,
+    file: &str,
+) -> Result<&'a FontMap, Plot2dError> {
+    fonts
+        .get(file)
+        .ok_or_else(|| Plot2dError::MissingFont(file.to_string()))
+}
+
+static MARGIN: [f32; 4] = [60.0, 20.0, 50.0, 30.0]; // left, right, bottom, top px
+static LABEL_GAP: f32 = 4.0; // px between tick marks or frame and labels
+static TITLE_GAP: f32 = 30.0; // px below frame for x axis title
+static LEGEND_PAD: f32 = 8.0; // px between top left corner of frame and legend
+static LEGEND_ROW: f32 = 18.0; // px height of legend entry
+static LEGEND_SAMPLE: f32 = 20.0; // px width of line sample before legend name
+
+extern crate thiserror;
+use crate::gl_wrap::{ProgramError, ShaderError, UniformError};
+use crate::scene::SceneError;
+use crate::text::{FontMapError, TextSceneError};
+use thiserror::Error;
+#[derive(Error, Debug)]
+pub enum Plot2dError {
+    #[error("{0}")]
+    Shader(#[from] ShaderError),
+    #[error("{0}")]
+    Program(#[from] ProgramError),
+    #[error("{0}")]
+    Uniform(#[from] UniformError),
+    #[error("Missing font map for '{0}'")]
+    MissingFont(String),
+    #[error("{0}")]
+    FontMap(#[from] FontMapError),
+    #[error("{0}")]
+    Scene(#[from] SceneError),
+    #[error("{0}")]
+    Text(#[from] TextSceneError),
+}
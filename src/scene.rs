@@ -1,81 +1,354 @@
 extern crate gl;
-use crate::gl_wrap::UniformError;
-use crate::gl_wrap::{Bind, Buffer, Drop, Program, Texture, Uniform, VertexArray};
+use crate::gl_wrap::{Bind, Buffer, Program, SharedTexture, Uniform, VertexArray};
+use crate::gl_wrap::{UniformError, UniformValue};
 use gl::types::GLenum;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// index of resource in scene, tagged with id of builder that issued it
+#[derive(Copy, Clone, PartialEq)]
+struct Handle {
+    scene: usize,
+    index: usize,
+}
+
+// handles to resources added to scene builder, only valid for scene built from it
+#[derive(Copy, Clone, PartialEq)]
+pub struct ProgramHandle(Handle);
+#[derive(Copy, Clone, PartialEq)]
+pub struct VaoHandle(Handle);
+#[derive(Copy, Clone, PartialEq)]
+pub struct BufferHandle(Handle);
+#[derive(Copy, Clone, PartialEq)]
+pub struct TextureHandle(Handle);
+#[derive(Copy, Clone, PartialEq)]
+pub struct UniformHandle(Handle);
+#[derive(Copy, Clone, PartialEq)]
+pub struct PassHandle(Handle);
 
 // struct containing all info for single gl draw operation
 pub struct DrawPass {
-    pub draw_type: GLenum,
-    pub start: i32,
-    pub count: i32,
-    pub inds: DrawInds,
+    draw_type: GLenum,
+    start: i32,
+    count: i32,
+    indexed: bool,          // start and count refer to vao element buffer
+    instances: Option<i32>, // draw repeated with per instance attributes
+    inds: DrawInds,
+    handle: PassHandle,
 }
 
-pub struct DrawInds {
-    pub program: usize,
-    pub vao: usize,
-    pub texture: Option<usize>,
-    pub uniform: Vec<usize>,
+struct DrawInds {
+    program: ProgramHandle,
+    vao: VaoHandle,
+    texture: Option<TextureHandle>,
+    uniform: Vec<UniformHandle>,
 }
 
 impl DrawPass {
-    pub fn draw(
+    // draw count u32 indices from start of vao element buffer instead of vertices
+    pub fn indexed(&mut self) -> &mut Self {
+        self.indexed = true;
+        self
+    }
+
+    // repeat draw once per instance, e.g. marker quad per data point
+    pub fn instanced(&mut self, instances: usize) -> &mut Self {
+        self.instances = Some(instances as i32);
+        self
+    }
+
+    // handle for updating pass of built scene, e.g. instances as points are streamed in
+    pub fn handle(&self) -> PassHandle {
+        self.handle
+    }
+
+    // nothing to draw, e.g. unused grid or series without points
+    fn is_empty(&self) -> bool {
+        self.count == 0 || self.instances == Some(0)
+    }
+
+    fn draw(
         &self,
         programs: &[Program],
         vaos: &[VertexArray],
-        textures: &[Texture],
+        textures: &[SharedTexture],
         uniforms: &[Uniform],
-    ) -> Result<(), UniformError> {
-        let program = &programs[self.inds.program];
+    ) {
+        let program = &programs[self.inds.program.0.index];
         program.bind();
-        vaos[self.inds.vao].bind();
-        if let Some(ind) = self.inds.texture {
-            textures[ind].bind();
+        vaos[self.inds.vao.0.index].bind();
+        if let Some(handle) = self.inds.texture {
+            textures[handle.0.index].bind();
         }
-        for &i in &self.inds.uniform {
-            uniforms[i].set()?;
+        for handle in &self.inds.uniform {
+            uniforms[handle.0.index].set();
         }
+        let offset = (self.start as usize * std::mem::size_of::<u32>()) as *const _;
         unsafe {
-            gl::DrawArrays(self.draw_type, self.start, self.count);
+            match (self.indexed, self.instances) {
+                (false, None) => gl::DrawArrays(self.draw_type, self.start, self.count),
+                (false, Some(instances)) => {
+                    gl::DrawArraysInstanced(self.draw_type, self.start, self.count, instances)
+                }
+                (true, None) => {
+                    gl::DrawElements(self.draw_type, self.count, gl::UNSIGNED_INT, offset)
+                }
+                (true, Some(instances)) => gl::DrawElementsInstanced(
+                    self.draw_type,
+                    self.count,
+                    gl::UNSIGNED_INT,
+                    offset,
+                    instances,
+                ),
+            }
         }
-        Ok(())
     }
 }
 
-// struct containing all gl resources and draw operations for complex scene
+// struct containing all gl resources and draw operations for complex scene,
+// freeing them when dropped unless shared with another owner
 pub struct Scene {
-    pub passes: Vec<DrawPass>,
-    pub programs: Vec<Program>,
-    pub vaos: Vec<VertexArray>,
-    pub buffers: Vec<Buffer>,
-    pub textures: Vec<Texture>,
-    pub uniforms: Vec<Uniform>,
+    id: usize,
+    passes: Vec<DrawPass>,
+    programs: Vec<Program>,
+    vaos: Vec<VertexArray>,
+    buffers: Vec<Buffer>,
+    textures: Vec<SharedTexture>,
+    uniforms: Vec<Uniform>,
 }
 
 impl Scene {
-    pub fn draw(&self) -> Result<(), UniformError> {
-        for pass in &self.passes {
+    // draw passes in order added, skipping those with nothing to draw
+    pub fn draw(&self) {
+        for pass in self.passes.iter().filter(|pass| !pass.is_empty()) {
             // do not pass in buffers since buffer state is stored in vaos
-            pass.draw(&self.programs, &self.vaos, &self.textures, &self.uniforms)?;
+            pass.draw(&self.programs, &self.vaos, &self.textures, &self.uniforms);
+        }
+    }
+
+    // update value of all uniforms with given name, e.g. mvp on camera change,
+    // leaving all unchanged if value does not fit any of them
+    pub fn set_uniform(
+        &mut self,
+        name: &str,
+        value: impl Into<UniformValue>,
+    ) -> Result<(), UniformError> {
+        let value = value.into();
+        for uniform in self.uniforms.iter().filter(|u| u.name() == name) {
+            uniform.check(&value)?;
+        }
+        for uniform in self.uniforms.iter_mut().filter(|u| u.name() == name) {
+            uniform.set_value(value.clone())?;
         }
         Ok(())
     }
+
+    // buffer added to builder, e.g. to write streamed vertices in place
+    pub fn buffer(&self, handle: BufferHandle) -> &Buffer {
+        &self.buffers[check(self.id, handle.0)]
+    }
+
+    // number of vertices or indices drawn by pass, keeping its start
+    pub fn set_count(&mut self, pass: PassHandle, count: usize) {
+        self.passes[check(self.id, pass.0)].count = count as i32;
+    }
+
+    // number of instances drawn by instanced pass, e.g. as points are streamed in
+    pub fn set_instances(&mut self, pass: PassHandle, instances: usize) {
+        self.passes[check(self.id, pass.0)].instances = Some(instances as i32);
+    }
 }
 
-impl Drop for Scene {
-    fn drop(&self) {
-        for program in &self.programs {
-            program.drop();
-        }
-        for vao in &self.vaos {
-            vao.drop();
+// collects scene resources, returning handles used to describe draw passes,
+// with passes checked against the resources they bind when scene is built
+pub struct SceneBuilder {
+    id: usize,
+    programs: Vec<Program>,
+    vaos: Vec<(VertexArray, ProgramHandle)>,
+    buffers: Vec<Buffer>,
+    textures: Vec<SharedTexture>,
+    uniforms: Vec<(Uniform, ProgramHandle)>,
+    passes: Vec<DrawPass>,
+}
+
+impl SceneBuilder {
+    pub fn new() -> Self {
+        Self {
+            id: NEXT_SCENE_ID.fetch_add(1, Ordering::Relaxed),
+            programs: Vec::new(),
+            vaos: Vec::new(),
+            buffers: Vec::new(),
+            textures: Vec::new(),
+            uniforms: Vec::new(),
+            passes: Vec::new(),
         }
-        for texture in &self.textures {
-            texture.drop();
+    }
+
+    fn handle(&self, index: usize) -> Handle {
+        Handle {
+            scene: self.id,
+            index,
         }
-        // references stored for buffers only to drop on scene deletion
-        for buffer in &self.buffers {
-            buffer.drop();
+    }
+
+    pub fn add_program(&mut self, program: Program) -> ProgramHandle {
+        self.programs.push(program);
+        ProgramHandle(self.handle(self.programs.len() - 1))
+    }
+
+    pub fn program(&self, handle: ProgramHandle) -> &Program {
+        &self.programs[check(self.id, handle.0)]
+    }
+
+    // vertex array with attributes located in given program
+    pub fn add_vao(&mut self, vao: VertexArray, program: ProgramHandle) -> VaoHandle {
+        check(self.id, program.0);
+        self.vaos.push((vao, program));
+        VaoHandle(self.handle(self.vaos.len() - 1))
+    }
+
+    pub fn add_buffer(&mut self, buffer: Buffer) -> BufferHandle {
+        self.buffers.push(buffer);
+        BufferHandle(self.handle(self.buffers.len() - 1))
+    }
+
+    pub fn buffer(&self, handle: BufferHandle) -> &Buffer {
+        &self.buffers[check(self.id, handle.0)]
+    }
+
+    pub fn add_texture(&mut self, texture: &SharedTexture) -> TextureHandle {
+        self.textures.push(Rc::clone(texture));
+        TextureHandle(self.handle(self.textures.len() - 1))
+    }
+
+    // uniform located in given program, only bindable in passes using that program
+    pub fn add_uniform(
+        &mut self,
+        program: ProgramHandle,
+        name: &str,
+        value: impl Into<UniformValue>,
+    ) -> Result<UniformHandle, UniformError> {
+        let uniform = Uniform::new(self.program(program), name, value)?;
+        self.uniforms.push((uniform, program));
+        Ok(UniformHandle(self.handle(self.uniforms.len() - 1)))
+    }
+
+    // draw count vertices from start of vao buffer with program, texture and uniforms,
+    // returning pass to be made indexed or instanced
+    pub fn add_pass(
+        &mut self,
+        draw_type: GLenum,
+        (start, count): (usize, usize),
+        program: ProgramHandle,
+        vao: VaoHandle,
+        texture: Option<TextureHandle>,
+        uniform: &[UniformHandle],
+    ) -> &mut DrawPass {
+        let handle = PassHandle(self.handle(self.passes.len()));
+        self.passes.push(DrawPass {
+            draw_type,
+            start: start as i32,
+            count: count as i32,
+            indexed: false,
+            instances: None,
+            inds: DrawInds {
+                program,
+                vao,
+                texture,
+                uniform: uniform.to_vec(),
+            },
+            handle,
+        });
+        self.passes.last_mut().unwrap()
+    }
+
+    // check each pass only binds resources of this scene matching its program
+    fn validate(&self) -> Result<(), SceneError> {
+        for (i, pass) in self.passes.iter().enumerate() {
+            let inds = &pass.inds;
+            let mut handles = vec![inds.program.0, inds.vao.0];
+            handles.extend(inds.texture.map(|t| t.0));
+            handles.extend(inds.uniform.iter().map(|u| u.0));
+            if handles.iter().any(|handle| handle.scene != self.id) {
+                return Err(SceneError::Foreign(i));
+            }
+            let texture_missing = inds
+                .texture
+                .is_some_and(|t| t.0.index >= self.textures.len());
+            if inds.program.0.index >= self.programs.len() || texture_missing {
+                return Err(SceneError::Missing(i));
+            }
+            match self.vaos.get(inds.vao.0.index) {
+                Some((vao, _)) if pass.indexed && !vao.has_elements() => {
+                    return Err(SceneError::NoElements(i))
+                }
+                Some((_, program)) if *program == inds.program => {}
+                Some(_) => return Err(SceneError::VaoProgram(i)),
+                None => return Err(SceneError::Missing(i)),
+            }
+            for (j, handle) in inds.uniform.iter().enumerate() {
+                let (uniform, program) = match self.uniforms.get(handle.0.index) {
+                    Some(entry) => entry,
+                    None => return Err(SceneError::Missing(i)),
+                };
+                let name = uniform.name().to_string();
+                if *program != inds.program {
+                    return Err(SceneError::UniformProgram(i, name));
+                }
+                // later value of same uniform would silently replace earlier one
+                let repeated = inds.uniform[..j]
+                    .iter()
+                    .any(|other| self.uniforms[other.0.index].0.name() == name);
+                if repeated {
+                    return Err(SceneError::DuplicateUniform(i, name));
+                }
+            }
         }
+        Ok(())
     }
+
+    // scene owning all added resources, which are freed with builder if any pass is invalid
+    pub fn build(self) -> Result<Scene, SceneError> {
+        self.validate()?;
+        Ok(Scene {
+            id: self.id,
+            programs: self.programs,
+            vaos: self.vaos.into_iter().map(|(vao, _)| vao).collect(),
+            buffers: self.buffers,
+            textures: self.textures,
+            uniforms: self
+                .uniforms
+                .into_iter()
+                .map(|(uniform, _)| uniform)
+                .collect(),
+            passes: self.passes,
+        })
+    }
+}
+
+// index of handle issued for scene, panicking on handle of another scene
+// like out of bounds index, since it would otherwise refer to unrelated resource
+fn check(scene: usize, handle: Handle) -> usize {
+    assert!(handle.scene == scene, "handle used with another scene");
+    handle.index
+}
+
+static NEXT_SCENE_ID: AtomicUsize = AtomicUsize::new(0);
+
+extern crate thiserror;
+use thiserror::Error;
+#[derive(Error, Debug)]
+pub enum SceneError {
+    #[error("Draw pass {0} refers to resource not added to scene")]
+    Missing(usize),
+    #[error("Draw pass {0} refers to resource added to another scene")]
+    Foreign(usize),
+    #[error("Draw pass {0} binds vertex array of another program")]
+    VaoProgram(usize),
+    #[error("Draw pass {0} is indexed but its vertex array has no element buffer")]
+    NoElements(usize),
+    #[error("Draw pass {0} binds uniform '{1}' of another program")]
+    UniformProgram(usize, String),
+    #[error("Draw pass {0} sets uniform '{1}' more than once")]
+    DuplicateUniform(usize, String),
 }
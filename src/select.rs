@@ -0,0 +1,143 @@
+extern crate gl;
+use crate::gl_wrap::{Buffer, Program, VertexArray};
+use crate::scene::{BufferHandle, PassHandle, Scene, SceneBuilder};
+use crate::vertices::PosVert;
+
+// shape of region dragged out in screen space
+#[derive(Copy, Clone, PartialEq)]
+pub enum SelectMode {
+    Box,
+    Lasso,
+}
+
+// region being dragged by user, stored as outline in window px
+pub struct Selection {
+    pub mode: SelectMode,
+    start: [f32; 2],
+    path: Vec<[f32; 2]>,
+}
+
+// gl scene for outline with vertex buffer sized to allow extending path in place
+pub struct SelectionScene {
+    pub scene: Scene,
+    outline: BufferHandle,
+    pass: PassHandle,
+    capacity: usize, // path points outline buffer can hold
+    written: usize,  // path points in outline buffer
+}
+
+impl SelectionScene {
+    // write path points changed since last update, returning false if buffer is full
+    pub fn update(&mut self, selection: &Selection) -> bool {
+        let path = &selection.path;
+        if path.len() > self.capacity {
+            return false;
+        }
+        // box outline replaced on each drag, lasso only extended
+        let start = match selection.mode {
+            SelectMode::Box => 0,
+            SelectMode::Lasso => self.written.min(path.len()),
+        };
+        let verts = to_verts(&path[start..]);
+        self.scene.buffer(self.outline).set_sub_data(start, &verts);
+        self.scene.set_count(self.pass, path.len());
+        self.written = path.len();
+        true
+    }
+}
+
+impl Selection {
+    pub fn new(mode: SelectMode, start: [f32; 2]) -> Self {
+        Self {
+            mode,
+            start,
+            path: vec![start],
+        }
+    }
+
+    // extend region to cursor, box mode keeps only opposite corner
+    pub fn drag_to(&mut self, cursor: [f32; 2]) {
+        match self.mode {
+            SelectMode::Box => {
+                let [x0, y0] = self.start;
+                let [x1, y1] = cursor;
+                self.path = vec![[x0, y0], [x1, y0], [x1, y1], [x0, y1]];
+            }
+            SelectMode::Lasso => self.path.push(cursor),
+        }
+    }
+
+    // even-odd test of point against closed outline
+    pub fn contains(&self, p: [f32; 2]) -> bool {
+        let mut inside = false;
+        let n = self.path.len();
+        for i in 0..n {
+            let [xi, yi] = self.path[i];
+            let [xj, yj] = self.path[(i + n - 1) % n];
+            if (yi > p[1]) != (yj > p[1]) && p[0] < (xj - xi) * (p[1] - yi) / (yj - yi) + xi {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    // closed outline drawn over plot in window px, with room for lasso to grow
+    pub fn get_scene(&self, viewport: [f32; 2]) -> Result<SelectionScene, SelectError> {
+        let capacity = (self.path.len() * 2).max(MIN_CAPACITY);
+        let mut scene = SceneBuilder::new();
+        const SCREEN_VERT: &str = "./shaders/screen_vert.glsl";
+        const SOLID_FRAG: &str = "./shaders/solid_frag.glsl";
+        let program = scene.add_program(Program::new_from_files(SCREEN_VERT, SOLID_FRAG)?);
+        let pos_loc = scene.program(program).get_attrib_location("position")?;
+        let vao = VertexArray::new();
+        let buffer = Buffer::new_with_capacity::<PosVert>(capacity, gl::DYNAMIC_DRAW);
+        let outline = scene.add_buffer(buffer);
+        vao.set_attribute::<PosVert>(pos_loc, 3, 0);
+        let vao = scene.add_vao(vao, program);
+        let uniforms = [
+            scene.add_uniform(program, "viewport", &viewport)?,
+            scene.add_uniform(program, "color", &OUTLINE_COLOR)?,
+        ];
+        let pass = scene
+            .add_pass(gl::LINE_LOOP, (0, 0), program, vao, None, &uniforms)
+            .handle();
+
+        // fill outline buffer as if whole path was just dragged out
+        let mut selection_scene = SelectionScene {
+            scene: scene.build()?,
+            outline,
+            pass,
+            capacity,
+            written: 0,
+        };
+        selection_scene.update(self);
+        Ok(selection_scene)
+    }
+}
+
+fn to_verts(path: &[[f32; 2]]) -> Vec<PosVert> {
+    path.iter()
+        .map(|&[x, y]| PosVert {
+            position: [x, y, 0.0],
+        })
+        .collect()
+}
+
+static OUTLINE_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.8];
+static MIN_CAPACITY: usize = 256; // outline points allocated for new selection
+
+extern crate thiserror;
+use crate::gl_wrap::{ProgramError, ShaderError, UniformError};
+use crate::scene::SceneError;
+use thiserror::Error;
+#[derive(Error, Debug)]
+pub enum SelectError {
+    #[error("{0}")]
+    Shader(#[from] ShaderError),
+    #[error("{0}")]
+    Program(#[from] ProgramError),
+    #[error("{0}")]
+    Uniform(#[from] UniformError),
+    #[error("{0}")]
+    Scene(#[from] SceneError),
+}
@@ -0,0 +1,454 @@
+extern crate gl;
+extern crate glam;
+use crate::gl_wrap::{Bind, Buffer, Program, VertexArray};
+use crate::plot::{Bounds, DataRange};
+use crate::scene::{BufferHandle, PassHandle, Scene, SceneBuilder};
+use crate::select::Selection;
+use crate::text::{FontMap, TextBatch, TextParams};
+use crate::vertices::{quad_inds, quad_range, PosVert, MARKER_QUAD};
+use glam::{Mat4, Vec3, Vec4};
+
+// named set of data points drawn as scatter markers
+pub struct Series {
+    pub name: String,
+    pub color: [f32; 4],
+    pub size: f32, // marker diameter in px
+    points: Vec<[f32; 3]>,
+    ring: Option<usize>, // keep only this many latest points
+    total: usize,        // points ever added, including those dropped from ring
+}
+
+// gl scene for series with marker buffer sized to allow appending points in place
+pub struct SeriesScene {
+    pub scene: Scene,
+    markers: BufferHandle,
+    marker_pass: PassHandle,
+    capacity: usize, // points marker buffer can hold
+    ring: bool,      // wrap writes around buffer instead of growing
+    written: usize,  // series total when marker buffer was last written
+}
+
+impl SeriesScene {
+    // write points added since last update, returning false if buffer is full
+    pub fn append(&mut self, series: &Series) -> bool {
+        let new = series.total - self.written;
+        if new == 0 {
+            return true;
+        }
+        if !self.ring && series.total > self.capacity {
+            return false;
+        }
+        let held = series.points.len();
+        let write = ring_write(self.written, series.total, held, self.capacity);
+        let verts = to_verts(&series.points[held - write.new..]);
+        let (head, tail) = verts.split_at(write.head);
+        self.scene
+            .buffer(self.markers)
+            .set_sub_data(write.start, head);
+        self.scene.buffer(self.markers).set_sub_data(0, tail);
+        self.scene.set_instances(self.marker_pass, write.instances);
+        self.written = series.total;
+        true
+    }
+
+    // map data units to drawn box without rewriting marker buffer
+    pub fn set_range(&mut self, range: &DataRange, bounds: &Bounds) -> Result<(), UniformError> {
+        self.scene.set_uniform("range_min", &range.min.to_array())?;
+        self.scene
+            .set_uniform("range_scale", &range.box_scale(bounds).to_array())
+    }
+}
+
+// single point of series found under cursor
+#[derive(Copy, Clone, PartialEq)]
+pub struct Pick {
+    pub series: usize,
+    pub index: usize,
+}
+
+impl Series {
+    pub fn new(name: &str, points: Vec<[f32; 3]>) -> Self {
+        Self {
+            name: name.to_string(),
+            color: [0.2, 0.6, 1.0, 1.0],
+            size: 6.0,
+            total: points.len(),
+            points,
+            ring: None,
+        }
+    }
+
+    // series showing only latest capacity points, stored in fixed size ring buffer on gpu
+    pub fn ring(name: &str, capacity: usize) -> Self {
+        let mut series = Self::new(name, vec![]);
+        series.ring = Some(capacity.max(1));
+        series
+    }
+
+    pub fn points(&self) -> &[[f32; 3]] {
+        &self.points
+    }
+
+    // add points to end, returning true if oldest points were dropped from ring
+    pub fn append(&mut self, mut points: Vec<[f32; 3]>) -> bool {
+        self.total += points.len();
+        self.points.append(&mut points);
+        self.trim()
+    }
+
+    pub fn set_points(&mut self, points: Vec<[f32; 3]>) {
+        self.total = points.len();
+        self.points = points;
+        self.trim();
+    }
+
+    // drop points older than ring capacity
+    fn trim(&mut self) -> bool {
+        match self.ring {
+            Some(capacity) if self.points.len() > capacity => {
+                self.points.drain(..self.points.len() - capacity);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // point in data units converted to position in drawn box
+    fn box_point(&self, index: usize, range: &DataRange, bounds: &Bounds) -> Vec3 {
+        range.to_box(Vec3::from(self.points[index]), bounds)
+    }
+
+    // logical px position of each point within viewport, none if behind camera
+    fn screen_points(
+        &self,
+        mvp: &Mat4,
+        viewport: [f32; 2],
+        range: &DataRange,
+        bounds: &Bounds,
+    ) -> Vec<Option<[f32; 2]>> {
+        (0..self.points.len())
+            .map(|index| {
+                let clip = *mvp * self.box_point(index, range, bounds).extend(1.0);
+                if clip.w <= 0.0 {
+                    return None;
+                }
+                let ndc: Vec4 = clip / clip.w;
+                Some([
+                    (ndc.x + 1.0) * 0.5 * viewport[0],
+                    (1.0 - ndc.y) * 0.5 * viewport[1],
+                ])
+            })
+            .collect()
+    }
+
+    // markers for all points, followed by highlighted markers for selected points,
+    // each drawn as one quad instanced per point in data units mapped to drawn box by shader
+    pub fn get_scene(
+        &self,
+        mvp: [f32; 16],
+        viewport: [f32; 2],
+        range: &DataRange,
+        bounds: &Bounds,
+        selected: &[usize],
+    ) -> Result<SeriesScene, SeriesError> {
+        let capacity = match self.ring {
+            Some(capacity) => capacity,
+            None => (self.total * 2).max(MIN_CAPACITY),
+        };
+        let selected_points: Vec<[f32; 3]> = selected.iter().map(|&i| self.points[i]).collect();
+        let selected_verts = to_verts(&selected_points);
+
+        let mut scene = SceneBuilder::new();
+        const MARKER_VERT: &str = "./shaders/marker_vert.glsl";
+        const MARKER_FRAG: &str = "./shaders/marker_frag.glsl";
+        let program = scene.add_program(Program::new_from_files(MARKER_VERT, MARKER_FRAG)?);
+        let cor_loc = scene.program(program).get_attrib_location("corner")?;
+        let pos_loc = scene.program(program).get_attrib_location("position")?;
+        let quad = scene.add_buffer(Buffer::new_from(&MARKER_QUAD, gl::STATIC_DRAW));
+        let quad_elements = scene.add_buffer(Buffer::new_from(&quad_inds(1), gl::STATIC_DRAW));
+        // vao pairing shared quad with points buffer, advanced once per instance
+        let marker_vao = |scene: &mut SceneBuilder, points: BufferHandle| {
+            let mut vao = VertexArray::new();
+            scene.buffer(quad).bind();
+            vao.set_attribute::<[f32; 2]>(cor_loc, 2, 0);
+            scene.buffer(points).bind();
+            vao.set_instance_attribute::<PosVert>(pos_loc, 3, 0);
+            vao.set_elements(scene.buffer(quad_elements));
+            scene.add_vao(vao, program)
+        };
+        // markers kept in dynamic buffer with room for streamed points
+        let buffer = Buffer::new_with_capacity::<PosVert>(capacity, gl::DYNAMIC_DRAW);
+        let markers = scene.add_buffer(buffer);
+        let vao = marker_vao(&mut scene, markers);
+        let selected = scene.add_buffer(Buffer::new_from(&selected_verts, gl::STATIC_DRAW));
+        let selected_vao = marker_vao(&mut scene, selected);
+        let shared = [
+            scene.add_uniform(program, "mvp", &mvp)?,
+            scene.add_uniform(program, "viewport", &viewport)?,
+            scene.add_uniform(program, "range_min", &range.min.to_array())?,
+            scene.add_uniform(program, "range_scale", &range.box_scale(bounds).to_array())?,
+        ];
+        let marker = [
+            scene.add_uniform(program, "size", &[self.size])?,
+            scene.add_uniform(program, "color", &self.color)?,
+        ];
+        let selected_marker = [
+            scene.add_uniform(program, "size", &[self.size + SELECT_GROW])?,
+            scene.add_uniform(program, "color", &SELECT_COLOR)?,
+        ];
+
+        // marker pass drawn without instances until points are appended
+        let quad_range = quad_range((0, MARKER_QUAD.len()));
+        let uniform = [shared.as_slice(), &marker].concat();
+        let marker_pass = scene
+            .add_pass(gl::TRIANGLES, quad_range, program, vao, None, &uniform)
+            .indexed()
+            .instanced(0)
+            .handle();
+        let uniform = [shared.as_slice(), &selected_marker].concat();
+        scene
+            .add_pass(
+                gl::TRIANGLES,
+                quad_range,
+                program,
+                selected_vao,
+                None,
+                &uniform,
+            )
+            .indexed()
+            .instanced(selected_verts.len());
+
+        let scene = scene.build()?;
+        // fill marker buffer as if held points were just appended
+        let mut series_scene = SeriesScene {
+            scene,
+            markers,
+            marker_pass,
+            capacity,
+            ring: self.ring.is_some(),
+            written: self.total - self.points.len(),
+        };
+        series_scene.append(self);
+        Ok(series_scene)
+    }
+
+    // tooltip with series name and data values, anchored to picked point in screen space
+    pub fn get_tooltip_scene(
+        &self,
+        index: usize,
+        mvp: [f32; 16],
+        range: &DataRange,
+        bounds: &Bounds,
+        param: &TextParams,
+        font: &FontMap,
+    ) -> Result<Scene, SeriesError> {
+        let [x, y, z] = self.points[index];
+        let name = font.literal(&self.name);
+        let label = format!("{}\nx = {:.3}\ny = {:.3}\nz = {:.3}", name, x, y, z);
+        let position = self.box_point(index, range, bounds).to_array();
+        let mut labels = TextBatch::new();
+        labels.group(font, param, None);
+        labels.add(&label, position)?;
+        let mut scene = SceneBuilder::new();
+        labels.add_to_scene(&mut scene, mvp)?;
+        Ok(scene.build()?)
+    }
+}
+
+// find point nearest cursor in logical px across all series, within marker radius plus margin
+pub fn pick(
+    series: &[Series],
+    mvp: [f32; 16],
+    viewport: [f32; 2],
+    cursor: [f32; 2],
+    range: &DataRange,
+    bounds: &Bounds,
+) -> Option<Pick> {
+    let mvp = Mat4::from_cols_array(&mvp);
+    let mut nearest: Option<(Pick, f32)> = None;
+    for (s, series) in series.iter().enumerate() {
+        let radius = series.size * 0.5 + PICK_MARGIN;
+        let screen = series.screen_points(&mvp, viewport, range, bounds);
+        for (index, px) in screen.into_iter().enumerate() {
+            let Some(px) = px else { continue };
+            let dist = ((px[0] - cursor[0]).powi(2) + (px[1] - cursor[1]).powi(2)).sqrt();
+            let closer = match nearest {
+                Some((_, best)) => dist < best,
+                None => true,
+            };
+            if dist <= radius && closer {
+                nearest = Some((Pick { series: s, index }, dist));
+            }
+        }
+    }
+    nearest.map(|(pick, _)| pick)
+}
+
+// indices of points of each series whose projection falls inside selection
+pub fn select(
+    series: &[Series],
+    selection: &Selection,
+    mvp: [f32; 16],
+    viewport: [f32; 2],
+    range: &DataRange,
+    bounds: &Bounds,
+) -> Vec<Vec<usize>> {
+    let mvp = Mat4::from_cols_array(&mvp);
+    series
+        .iter()
+        .map(|series| {
+            let screen = series.screen_points(&mvp, viewport, range, bounds);
+            let inside = |(_, px): &(usize, Option<[f32; 2]>)| match px {
+                Some(px) => selection.contains(*px),
+                None => false,
+            };
+            screen
+                .into_iter()
+                .enumerate()
+                .filter(inside)
+                .map(|(i, _)| i)
+                .collect()
+        })
+        .collect()
+}
+
+// marker buffer write for points appended since last write, point with series
+// index i being stored at i % capacity, so writes wrap from end of buffer to start
+#[derive(Debug, PartialEq)]
+struct RingWrite {
+    new: usize,       // latest held points to write
+    start: usize,     // buffer index of first of them
+    head: usize,      // points written from start before wrapping to index 0
+    instances: usize, // points in buffer after write
+}
+
+// write of points added to series since its total was written, of which
+// series still holds the latest held points
+fn ring_write(written: usize, total: usize, held: usize, capacity: usize) -> RingWrite {
+    // only latest points still held by series once ring has wrapped
+    let new = (total - written).min(held).min(capacity);
+    let start = (total - new) % capacity;
+    RingWrite {
+        new,
+        start,
+        head: new.min(capacity - start),
+        instances: total.min(capacity),
+    }
+}
+
+fn to_verts(points: &[[f32; 3]]) -> Vec<PosVert> {
+    points
+        .iter()
+        .map(|&position| PosVert { position })
+        .collect()
+}
+
+static PICK_MARGIN: f32 = 4.0; // px around marker still counted as hovering
+static MIN_CAPACITY: usize = 256; // points allocated for marker buffer of small series
+static SELECT_GROW: f32 = 4.0; // px added to diameter of selected markers
+static SELECT_COLOR: [f32; 4] = [1.0, 0.8, 0.1, 1.0];
+
+extern crate thiserror;
+use crate::gl_wrap::{ProgramError, ShaderError, UniformError};
+use crate::scene::SceneError;
+use crate::text::{FontMapError, TextSceneError};
+use thiserror::Error;
+#[derive(Error, Debug)]
+pub enum SeriesError {
+    #[error("{0}")]
+    Shader(#[from] ShaderError),
+    #[error("{0}")]
+    Program(#[from] ProgramError),
+    #[error("{0}")]
+    Uniform(#[from] UniformError),
+    #[error("{0}")]
+    FontMap(#[from] FontMapError),
+    #[error("{0}")]
+    Scene(#[from] SceneError),
+    #[error("{0}")]
+    Text(#[from] TextSceneError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(range: std::ops::Range<usize>) -> Vec<[f32; 3]> {
+        range.map(|i| [i as f32, 0.0, 0.0]).collect()
+    }
+
+    // apply write to buffer as scene append does, returning instance count
+    fn write(buffer: &mut [[f32; 3]], written: &mut usize, series: &Series) -> usize {
+        let held = series.points.len();
+        let write = ring_write(*written, series.total, held, buffer.len());
+        let (head, tail) = series.points[held - write.new..].split_at(write.head);
+        buffer[write.start..write.start + head.len()].copy_from_slice(head);
+        buffer[..tail.len()].copy_from_slice(tail);
+        *written = series.total;
+        write.instances
+    }
+
+    // every held point at slot of its series index
+    fn assert_held(buffer: &[[f32; 3]], series: &Series) {
+        let first = series.total - series.points.len();
+        for (i, point) in series.points.iter().enumerate() {
+            assert_eq!(buffer[(first + i) % buffer.len()], *point);
+        }
+    }
+
+    #[test]
+    fn writes_wrap_around_ring() {
+        let mut series = Series::ring("ring", 8);
+        let mut buffer = [[-1.0; 3]; 8];
+        let mut written = 0;
+        series.append(points(0..6));
+        assert_eq!(write(&mut buffer, &mut written, &series), 6);
+        series.append(points(6..11));
+        let write_at = ring_write(written, series.total, 8, 8);
+        assert_eq!(
+            write_at,
+            RingWrite {
+                new: 5,
+                start: 6,
+                head: 2,
+                instances: 8
+            }
+        );
+        assert_eq!(write(&mut buffer, &mut written, &series), 8);
+        assert_held(&buffer, &series);
+    }
+
+    #[test]
+    fn append_beyond_capacity_keeps_latest() {
+        let mut series = Series::ring("ring", 8);
+        let mut buffer = [[-1.0; 3]; 8];
+        let mut written = 0;
+        series.append(points(0..3));
+        write(&mut buffer, &mut written, &series);
+        series.append(points(3..23));
+        let write_at = ring_write(written, series.total, 8, 8);
+        assert_eq!((write_at.new, write_at.start, write_at.head), (8, 7, 1));
+        assert_eq!(write(&mut buffer, &mut written, &series), 8);
+        assert_held(&buffer, &series);
+        assert_eq!(series.points, points(15..23));
+    }
+
+    #[test]
+    fn replace_after_wrap_rewrites_from_start() {
+        let mut series = Series::ring("ring", 8);
+        let mut buffer = [[-1.0; 3]; 8];
+        let mut written = 0;
+        series.append(points(0..13));
+        write(&mut buffer, &mut written, &series);
+        // replaced series gets new scene, written up to points it no longer holds
+        series.set_points(points(100..103));
+        let mut buffer = [[-1.0; 3]; 8];
+        let mut written = series.total - series.points.len();
+        assert_eq!(ring_write(written, series.total, 3, 8).start, 0);
+        assert_eq!(write(&mut buffer, &mut written, &series), 3);
+        assert_held(&buffer, &series);
+        series.append(points(103..110));
+        assert_eq!(write(&mut buffer, &mut written, &series), 8);
+        assert_held(&buffer, &series);
+    }
+}
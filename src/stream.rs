@@ -0,0 +1,69 @@
+extern crate glutin;
+use glutin::event_loop::{EventLoopClosed, EventLoopProxy};
+use std::sync::mpsc::{SendError, Sender};
+
+// new data for series sent from another thread while plot is displayed
+pub enum SeriesData {
+    Append(Vec<[f32; 3]>),
+    Replace(Vec<[f32; 3]>),
+}
+
+pub struct SeriesUpdate {
+    pub series: usize,
+    pub data: SeriesData,
+}
+
+// thread safe handle for updating single series of displayed plot
+#[derive(Clone)]
+pub struct SeriesHandle {
+    series: usize,
+    sender: Sender<SeriesUpdate>,
+    proxy: EventLoopProxy<()>,
+}
+
+impl SeriesHandle {
+    pub fn new(series: usize, sender: Sender<SeriesUpdate>, proxy: EventLoopProxy<()>) -> Self {
+        Self {
+            series,
+            sender,
+            proxy,
+        }
+    }
+
+    pub fn append(&self, points: Vec<[f32; 3]>) -> Result<(), StreamError> {
+        self.send(SeriesData::Append(points))
+    }
+
+    #[allow(dead_code)]
+    pub fn set_data(&self, points: Vec<[f32; 3]>) -> Result<(), StreamError> {
+        self.send(SeriesData::Replace(points))
+    }
+
+    // queue data then wake event loop to apply it and redraw
+    fn send(&self, data: SeriesData) -> Result<(), StreamError> {
+        let series = self.series;
+        self.sender.send(SeriesUpdate { series, data })?;
+        self.proxy.send_event(())?;
+        Ok(())
+    }
+}
+
+extern crate thiserror;
+use thiserror::Error;
+#[derive(Error, Debug)]
+pub enum StreamError {
+    #[error("Plot window closed")]
+    Closed,
+}
+
+impl From<SendError<SeriesUpdate>> for StreamError {
+    fn from(_: SendError<SeriesUpdate>) -> Self {
+        StreamError::Closed
+    }
+}
+
+impl From<EventLoopClosed<()>> for StreamError {
+    fn from(_: EventLoopClosed<()>) -> Self {
+        StreamError::Closed
+    }
+}
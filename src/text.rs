@@ -1,21 +1,27 @@
 extern crate fontdue;
 extern crate gl;
 extern crate glam;
-use crate::gl_wrap::{Bind, Buffer, Drop, Program, Texture, TextureFramebuffer, VertexArray};
+use crate::gl_wrap::{
+    Bind, Buffer, Program, SharedTexture, Texture, TextureFramebuffer, VertexArray,
+};
+use crate::markup::{self, TextRun};
+use crate::scene::{PassHandle, SceneBuilder};
 use crate::vertices::{bmp_arr, bmp_vert, BitmapVert};
-use crate::vertices::{bmp_to_text_vert, TextVert};
+use crate::vertices::{bmp_to_text_vert, quad_inds, quad_range, TextVert};
 use fontdue::{Font, FontSettings};
 use std::collections::HashMap;
-use std::ffi::CString;
 use std::fs;
+use std::rc::Rc;
 
 pub struct FontMapper {
     program: Program,
     vao: VertexArray,
+    #[allow(dead_code)] // quad vertices read through vao, owned to free with mapper
     buffer: Buffer,
     uniforms: FontMapperUniforms,
     chars: Vec<char>,
-    window_size: [i32; 2],
+    window_size: [i32; 2], // viewport restored after rendering font maps
+    viewport_height: i32,  // height of viewport text is drawn in, e.g. figure cell
 }
 
 struct FontMapperUniforms {
@@ -24,7 +30,11 @@ struct FontMapperUniforms {
 }
 
 impl FontMapper {
-    pub fn new(window_width: i32, window_height: i32) -> Result<Self, FontMapperError> {
+    pub fn new(
+        window_width: i32,
+        window_height: i32,
+        viewport_height: i32,
+    ) -> Result<Self, FontMapperError> {
         // init gl resources for font bitmap creation
         const VERT: &str = "./shaders/bitmap_vert.glsl";
         const FRAG: &str = "./shaders/bitmap_frag.glsl";
@@ -38,18 +48,14 @@ impl FontMapper {
 
         // init program uniforms / get locations
         program.bind();
-        let uniforms: FontMapperUniforms;
-        let char_size_cname = CString::new("char_size")?;
-        let offset_cname = CString::new("offset")?;
-        let map_size_cname = CString::new("map_size")?;
+        // store uniform locations set during font map generation
+        let uniforms = FontMapperUniforms {
+            char_size: program.get_uniform_location("char_size")?,
+            offset: program.get_uniform_location("offset")?,
+        };
+        // don't need to store map size location since value is static and set once
+        let map_size_loc = program.get_uniform_location("map_size")?;
         unsafe {
-            // store uniform locations set during font map generation
-            uniforms = FontMapperUniforms {
-                char_size: gl::GetUniformLocation(program.id, char_size_cname.as_ptr()),
-                offset: gl::GetUniformLocation(program.id, offset_cname.as_ptr()),
-            };
-            // don't need to store map size location since value is static and set once
-            let map_size_loc = gl::GetUniformLocation(program.id, map_size_cname.as_ptr());
             gl::Uniform2fv(map_size_loc, 1, &MAP_SIZE[0]);
         }
 
@@ -63,6 +69,7 @@ impl FontMapper {
             chars,
             uniforms,
             window_size,
+            viewport_height,
         })
     }
 
@@ -111,7 +118,7 @@ impl FontMapper {
                 gl::Uniform2fv(self.uniforms.offset, 1, &offset[0]);
                 gl::DrawArrays(gl::TRIANGLE_STRIP, 0, NUM_VERTEX);
             }
-            texture.drop(); // free texture since only one draw needed
+            drop(texture); // free texture since only one draw needed
 
             let start_ind = vertices.len();
             indices.insert(self.chars[i], start_ind);
@@ -129,19 +136,20 @@ impl FontMapper {
             let mut quad = FontMapper::get_quad(w, h, tpx, tnx, tpy, tny);
             vertices.append(&mut quad);
         }
-        // free texture framebuffer for finished font map and bind default
+        // bind default framebuffer, freeing texture framebuffer once font map holds texture
         framebuffer.bind_default();
-        framebuffer.drop();
 
         // get conversion value for px -> gl clip space
-        let font_scale = 4.0 / (self.window_size[1] as f32 * line_height);
+        let font_scale = 4.0 / (self.viewport_height as f32 * line_height);
 
         // return finished font map
         let fontmap = FontMap {
-            texture: framebuffer.texture,
+            texture: Rc::clone(&framebuffer.texture),
             verts: vertices,
             inds: indices,
             scale: font_scale,
+            line_height,
+            baseline: avg_alignment,
         };
         Ok(fontmap)
     }
@@ -164,7 +172,7 @@ impl FontMapper {
         Ok(framebuffer)
     }
 
-    // get quad vertex data from dims and +/- texture coords
+    // get quad corner vertex data from dims and +/- texture coords
     fn get_quad(w: f32, h: f32, tpx: f32, tnx: f32, tpy: f32, tny: f32) -> Vec<BitmapVert> {
         let w2 = w * 0.5;
         let h2 = h * 0.5;
@@ -172,9 +180,7 @@ impl FontMapper {
             [w2, h2, tpx, tpy],
             [-w2, h2, tnx, tpy],
             [-w2, -h2, tnx, tny],
-            [-w2, -h2, tnx, tny],
-            [w2, -h2, tpx, tny],
-            [w2, h2, tpx, tpy]
+            [w2, -h2, tpx, tny]
         ]
     }
 
@@ -190,19 +196,13 @@ impl FontMapper {
     }
 }
 
-impl Drop for FontMapper {
-    fn drop(&self) {
-        self.program.drop();
-        self.vao.drop();
-        self.buffer.drop();
-    }
-}
-
 pub struct FontMap {
-    pub texture: Texture,
+    pub texture: SharedTexture,
     pub verts: Vec<BitmapVert>,
     pub inds: HashMap<char, usize>,
     pub scale: f32,
+    pub line_height: f32,
+    pub baseline: f32,
 }
 
 impl FontMap {
@@ -213,52 +213,416 @@ impl FontMap {
         position: [f32; 3],
     ) -> Result<Vec<TextVert>, FontMapError> {
         let mut vertices = Vec::<TextVert>::new();
-        let mut offset: f32 = 0.0;
-        for c in label.chars() {
-            if let ' ' = c {
-                // add fixed width for space character
-                offset += params.size;
-                continue;
-            }
-            // get start index of vertex data if char exists in font texture
-            let vert_ind = match self.inds.get(&c) {
-                Some(&index) => index,
-                None => return Err(FontMapError::Character(c)),
+        let line_step = self.line_height * params.line_spacing;
+        let lines: Vec<&str> = label.split('\n').collect();
+        for (i, line) in lines.iter().enumerate() {
+            let start = vertices.len();
+            let runs = markup::parse(line)?;
+            let width = self.layout_line(&runs, params, position, &mut vertices)?;
+            // align line horizontally about origin, lines stacked down from origin
+            let x_shift = match params.anchor.h {
+                HAlign::Left => 0.0,
+                HAlign::Center => -width * 0.5,
+                HAlign::Right => -width,
             };
-            // character width / 2 from first vertex x coordinate
-            let char_spacing = self.verts[vert_ind].position[0] + params.kearning;
-            offset += char_spacing;
-            for i in 0..VERT_PER_CHAR {
-                let mut vert = bmp_to_text_vert!(self.verts[i + vert_ind], position);
-                vert.offset[0] += offset; // layout text on x axis
-                vertices.push(vert);
+            let y_shift = -(i as f32) * line_step;
+            for vert in &mut vertices[start..] {
+                vert.offset[0] += x_shift;
+                vert.offset[1] += y_shift;
             }
-            offset += char_spacing;
         }
-        // center text about origin
-        let mid_width = offset * 0.5;
+        // align text block vertically about origin
+        let block_height = (lines.len() - 1) as f32 * line_step;
+        let half_line = self.line_height * 0.5;
+        let y_shift = match params.anchor.v {
+            VAlign::Top => -half_line,
+            VAlign::Baseline => self.baseline,
+            VAlign::Middle => block_height * 0.5,
+            VAlign::Bottom => block_height + half_line,
+        };
         for vert in &mut vertices {
-            vert.offset[0] -= mid_width;
+            vert.offset[1] += y_shift;
         }
         Ok(vertices)
     }
+
+    // user given text escaped from markup, with glyphs missing from font map replaced
+    // so labels such as series names always lay out
+    pub fn literal(&self, text: &str) -> String {
+        let shown: String = text
+            .chars()
+            .map(
+                |c| match c == ' ' || c == '\n' || self.inds.contains_key(&c) {
+                    true => c,
+                    false => FALLBACK_CHAR,
+                },
+            )
+            .collect();
+        markup::escape(&shown)
+    }
+
+    // append vertices for single line of styled runs starting at x = 0, returning line width
+    fn layout_line(
+        &self,
+        runs: &[TextRun],
+        params: &TextParams,
+        position: [f32; 3],
+        vertices: &mut Vec<TextVert>,
+    ) -> Result<f32, FontMapError> {
+        let mut offset: f32 = 0.0;
+        for run in runs {
+            let scale = run.style.scale;
+            // keep scaled glyphs on base line before applying script rise
+            let y_shift = self.baseline * (scale - 1.0) + run.style.rise * self.line_height;
+            for c in run.text.chars() {
+                if let ' ' = c {
+                    // add fixed width for space character
+                    offset += params.size * scale;
+                    continue;
+                }
+                // get start index of vertex data if char exists in font texture
+                let vert_ind = match self.inds.get(&c) {
+                    Some(&index) => index,
+                    None => return Err(FontMapError::Character(c)),
+                };
+                // character width / 2 from first vertex x coordinate
+                let char_spacing = self.verts[vert_ind].position[0] * scale + params.kearning;
+                offset += char_spacing;
+                for i in 0..VERT_PER_CHAR {
+                    let mut vert = bmp_to_text_vert!(self.verts[i + vert_ind], position);
+                    vert.offset[0] = vert.offset[0] * scale + offset; // layout text on x axis
+                    vert.offset[1] = vert.offset[1] * scale + y_shift;
+                    vertices.push(vert);
+                }
+                offset += char_spacing;
+            }
+        }
+        Ok(offset)
+    }
+
+    // get background quad and border line verts surrounding laid out label verts
+    pub fn get_box_verts(
+        &self,
+        text: &[TextVert],
+        params: &TextParams,
+    ) -> (Vec<TextVert>, Vec<TextVert>) {
+        let (position, background) = match (text.first(), &params.background) {
+            (Some(vert), Some(background)) => (vert.position, background),
+            _ => return (vec![], vec![]),
+        };
+        let mut min = [f32::MAX, f32::MAX];
+        let mut max = [f32::MIN, f32::MIN];
+        for vert in text {
+            let offset = vert.offset;
+            for i in 0..2 {
+                min[i] = min[i].min(offset[i]);
+                max[i] = max[i].max(offset[i]);
+            }
+        }
+        // convert padding from px to font map units
+        let pad = background.padding * self.line_height / (2.0 * params.size);
+        let (l, r, b, t) = (min[0] - pad, max[0] + pad, min[1] - pad, max[1] + pad);
+        let corner = |x: f32, y: f32| TextVert {
+            position,
+            offset: [x, y],
+            texcoord: [0.0, 0.0],
+        };
+        let fill = vec![
+            corner(r, t),
+            corner(l, t),
+            corner(l, b),
+            corner(l, b),
+            corner(r, b),
+            corner(r, t),
+        ];
+        let border = match background.border {
+            Some(_) => vec![
+                corner(l, t),
+                corner(r, t),
+                corner(r, t),
+                corner(r, b),
+                corner(r, b),
+                corner(l, b),
+                corner(l, b),
+                corner(l, t),
+            ],
+            None => vec![],
+        };
+        (fill, border)
+    }
+}
+
+// labels with background boxes collected for one scene, in groups sharing font
+// and text params, each group drawn as box fills, box borders then glyphs
+pub struct TextBatch<'a> {
+    text: Vec<TextVert>,
+    fills: Vec<TextVert>,
+    borders: Vec<TextVert>,
+    groups: Vec<TextGroup<'a>>,
+}
+
+struct TextGroup<'a> {
+    font: &'a FontMap,
+    param: &'a TextParams,
+    alignment: Option<[f32; 3]>, // direction text is turned along, facing camera if none
+    text: (usize, usize),
+    fill: (usize, usize),
+    border: (usize, usize),
 }
 
-impl Drop for FontMap {
-    fn drop(&self) {
-        self.texture.drop();
+impl<'a> TextBatch<'a> {
+    pub fn new() -> Self {
+        Self {
+            text: Vec::new(),
+            fills: Vec::new(),
+            borders: Vec::new(),
+            groups: Vec::new(),
+        }
+    }
+
+    // start group that following labels are added to,
+    // either all or none of the groups in batch must have alignment
+    pub fn group(&mut self, font: &'a FontMap, param: &'a TextParams, alignment: Option<[f32; 3]>) {
+        self.groups.push(TextGroup {
+            font,
+            param,
+            alignment,
+            text: (self.text.len(), 0),
+            fill: (self.fills.len(), 0),
+            border: (self.borders.len(), 0),
+        });
+    }
+
+    // lay out label at position with params of current group
+    pub fn add(&mut self, label: &str, position: [f32; 3]) -> Result<(), FontMapError> {
+        let group = self.groups.last().expect("label added before text group");
+        let verts = group.font.get_verts(label, group.param, position)?;
+        self.add_verts(verts);
+        Ok(())
+    }
+
+    // add laid out label to current group, e.g. after turning its offsets,
+    // with background box fitted to its verts
+    pub fn add_verts(&mut self, mut verts: Vec<TextVert>) {
+        let group = self
+            .groups
+            .last_mut()
+            .expect("label added before text group");
+        let (mut fill, mut border) = group.font.get_box_verts(&verts, group.param);
+        group.text.1 += verts.len();
+        group.fill.1 += fill.len();
+        group.border.1 += border.len();
+        self.text.append(&mut verts);
+        self.fills.append(&mut fill);
+        self.borders.append(&mut border);
+    }
+
+    // add text and box programs, buffers and passes to scene,
+    // returning box fill, box border and text pass per group
+    pub fn add_to_scene(
+        self,
+        scene: &mut SceneBuilder,
+        mvp: [f32; 16],
+    ) -> Result<Vec<[PassHandle; 3]>, TextSceneError> {
+        let aligned = self.groups.first().is_some_and(|g| g.alignment.is_some());
+        if self.groups.iter().any(|g| g.alignment.is_some() != aligned) {
+            return Err(TextSceneError::MixedAlignment);
+        }
+        let text_vert = match aligned {
+            true => "./shaders/text_align_vert.glsl",
+            false => "./shaders/text_vert.glsl",
+        };
+        const TEXT_FRAG: &str = "./shaders/text_frag.glsl";
+        let text_program = scene.add_program(Program::new_from_files(text_vert, TEXT_FRAG)?);
+        let program = scene.program(text_program);
+        let text_pos_loc = program.get_attrib_location("position")?;
+        let text_off_loc = program.get_attrib_location("offset")?;
+        let text_tco_loc = program.get_attrib_location("a_texCoord")?;
+        let mut text_vao = VertexArray::new();
+        scene.add_buffer(Buffer::new_from(&self.text, gl::STATIC_DRAW));
+        text_vao.set_attribute::<TextVert>(text_pos_loc, 3, 0);
+        text_vao.set_attribute::<TextVert>(text_off_loc, 2, 3);
+        text_vao.set_attribute::<TextVert>(text_tco_loc, 2, 5);
+        // glyph quads drawn as two indexed triangles each
+        let glyph_inds = quad_inds(self.text.len() / VERT_PER_CHAR);
+        let glyph_inds = scene.add_buffer(Buffer::new_from(&glyph_inds, gl::STATIC_DRAW));
+        text_vao.set_elements(scene.buffer(glyph_inds));
+        let text_vao = scene.add_vao(text_vao, text_program);
+
+        // boxes share text vertex shader, with all fills followed by all borders
+        const BOX_FRAG: &str = "./shaders/solid_frag.glsl";
+        let box_program = scene.add_program(Program::new_from_files(text_vert, BOX_FRAG)?);
+        let program = scene.program(box_program);
+        let box_pos_loc = program.get_attrib_location("position")?;
+        let box_off_loc = program.get_attrib_location("offset")?;
+        let box_vao = VertexArray::new();
+        let box_verts = [self.fills.as_slice(), &self.borders].concat();
+        scene.add_buffer(Buffer::new_from(&box_verts, gl::STATIC_DRAW));
+        box_vao.set_attribute::<TextVert>(box_pos_loc, 3, 0);
+        box_vao.set_attribute::<TextVert>(box_off_loc, 2, 3);
+        let box_vao = scene.add_vao(box_vao, box_program);
+
+        let text_mvp = scene.add_uniform(text_program, "mvp", &mvp)?;
+        let box_mvp = scene.add_uniform(box_program, "mvp", &mvp)?;
+        let mut passes = Vec::new();
+        for group in &self.groups {
+            let param = group.param;
+            let scale = [group.font.scale * param.size];
+            let mut text_uniforms = vec![
+                text_mvp,
+                scene.add_uniform(text_program, "scale", &scale)?,
+                scene.add_uniform(text_program, "color", &param.text_color())?,
+            ];
+            let mut box_uniforms = vec![box_mvp, scene.add_uniform(box_program, "scale", &scale)?];
+            if let Some(alignment) = group.alignment {
+                text_uniforms.push(scene.add_uniform(text_program, "alignment", &alignment)?);
+                box_uniforms.push(scene.add_uniform(box_program, "alignment", &alignment)?);
+            }
+            let fill_color = scene.add_uniform(box_program, "color", &param.fill_color())?;
+            let border_color = scene.add_uniform(box_program, "color", &param.border_color())?;
+            let texture = scene.add_texture(&group.font.texture);
+            let (border_start, border_count) = group.border;
+            let border_range = (self.fills.len() + border_start, border_count);
+            let fill = scene
+                .add_pass(
+                    gl::TRIANGLES,
+                    group.fill,
+                    box_program,
+                    box_vao,
+                    None,
+                    &[box_uniforms.as_slice(), &[fill_color]].concat(),
+                )
+                .handle();
+            let border = scene
+                .add_pass(
+                    gl::LINES,
+                    border_range,
+                    box_program,
+                    box_vao,
+                    None,
+                    &[box_uniforms.as_slice(), &[border_color]].concat(),
+                )
+                .handle();
+            let text = scene
+                .add_pass(
+                    gl::TRIANGLES,
+                    quad_range(group.text),
+                    text_program,
+                    text_vao,
+                    Some(texture),
+                    &text_uniforms,
+                )
+                .indexed()
+                .handle();
+            passes.push([fill, border, text]);
+        }
+        Ok(passes)
     }
 }
 
+#[derive(Clone)]
 pub struct TextParams {
     pub font: String,
     pub size: f32,
     pub kearning: f32,
+    pub line_spacing: f32,
+    pub anchor: TextAnchor,
+    pub color: [f32; 4],
+    pub opacity: f32,
+    pub background: Option<TextBox>,
+}
+
+impl TextParams {
+    pub fn new(size: f32) -> Self {
+        Self {
+            font: DEFAULT_FONT.to_string(),
+            size,
+            kearning: 0.0,
+            line_spacing: 1.0,
+            anchor: TextAnchor::center(),
+            color: [1.0, 1.0, 1.0, 1.0],
+            opacity: 1.0,
+            background: None,
+        }
+    }
+
+    // colors with label opacity applied for text, box fill and box border
+    pub fn text_color(&self) -> [f32; 4] {
+        self.apply_opacity(self.color)
+    }
+
+    pub fn fill_color(&self) -> [f32; 4] {
+        match &self.background {
+            Some(background) => self.apply_opacity(background.fill),
+            None => [0.0; 4],
+        }
+    }
+
+    pub fn border_color(&self) -> [f32; 4] {
+        match &self.background {
+            Some(TextBox {
+                border: Some(border),
+                ..
+            }) => self.apply_opacity(*border),
+            _ => [0.0; 4],
+        }
+    }
+
+    fn apply_opacity(&self, color: [f32; 4]) -> [f32; 4] {
+        [color[0], color[1], color[2], color[3] * self.opacity]
+    }
+}
+
+// optional box drawn behind label, padding in px
+#[derive(Clone)]
+pub struct TextBox {
+    pub fill: [f32; 4],
+    pub border: Option<[f32; 4]>,
+    pub padding: f32,
+}
+
+// position of text block relative to its anchor point
+#[derive(Clone)]
+pub struct TextAnchor {
+    pub h: HAlign,
+    pub v: VAlign,
 }
 
-pub const VERT_PER_CHAR: usize = 6; // num vertices per char in output vertex data
+impl TextAnchor {
+    pub fn new(h: HAlign, v: VAlign) -> Self {
+        Self { h, v }
+    }
+
+    pub fn center() -> Self {
+        Self::new(HAlign::Center, VAlign::Middle)
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Clone)]
+pub enum HAlign {
+    Left,
+    Center,
+    Right,
+}
+
+#[allow(dead_code)]
+#[derive(Clone)]
+pub enum VAlign {
+    Top,
+    Baseline,
+    Middle,
+    Bottom,
+}
+
+pub const VERT_PER_CHAR: usize = 4; // quad corners per char, drawn with vertices::quad_inds
 pub const DEFAULT_FONT: &str = "./resources/Ubuntu-Regular.ttf";
-static CHAR_SET: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789.";
+static CHAR_SET: &str = concat!(
+    "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789",
+    ".,:;!?()[]{}+-*/=<>%_^|\\'",
+    "αβγδεζηθικλμνξπρστυφχψωΓΔΘΛΞΠΣΥΦΨΩ±×·°∞∂²³"
+);
+static FALLBACK_CHAR: char = '?'; // shown in place of chars missing from font map
 static FONT_SIZE: f32 = 30.0;
 static FONT_SUPERSAMPLE: f32 = 3.0;
 static MAP_SIZE: [f32; 2] = [1024.0, 512.0];
@@ -271,7 +635,8 @@ static VERTICES: [BitmapVert; 4] = bmp_arr![
 ];
 
 extern crate thiserror;
-use crate::gl_wrap::{FramebufferError, ProgramError};
+use crate::gl_wrap::{FramebufferError, ProgramError, ShaderError, UniformError};
+use crate::markup::MarkupError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -283,8 +648,6 @@ pub enum FontMapperError {
     #[error("{0}")]
     Io(#[from] std::io::Error),
     #[error("{0}")]
-    String(#[from] std::ffi::NulError),
-    #[error("{0}")]
     Font(String),
 }
 
@@ -298,4 +661,18 @@ impl From<&str> for FontMapperError {
 pub enum FontMapError {
     #[error("Invalid character '{0}'")]
     Character(char),
+    #[error("{0}")]
+    Markup(#[from] MarkupError),
+}
+
+#[derive(Error, Debug)]
+pub enum TextSceneError {
+    #[error("{0}")]
+    Shader(#[from] ShaderError),
+    #[error("{0}")]
+    Program(#[from] ProgramError),
+    #[error("{0}")]
+    Uniform(#[from] UniformError),
+    #[error("Text groups in one scene must all or none have alignment")]
+    MixedAlignment,
 }
@@ -1,39 +1,46 @@
 extern crate alloc;
 extern crate gl;
 extern crate glam;
-use crate::gl_wrap::{Buffer, Program, Uniform, VertexArray};
-use crate::plot::Bounds;
-use crate::scene::{DrawInds, DrawPass, Scene};
-use crate::text::{FontMap, TextParams, DEFAULT_FONT};
-use crate::vertices::{pos_vert, PosVert, TextVert};
-
-pub struct TickLabels {
-    pub x: bool,
-    pub y: bool,
-    pub z: bool,
-    pub param: TextParams,
-}
+use crate::axis::{AxisPlacement, EdgePlacement};
+use crate::gl_wrap::{Buffer, Program, VertexArray};
+use crate::markup;
+use crate::plot::{Bounds, DataRange};
+use crate::scene::{Scene, SceneBuilder};
+use crate::text::{FontMap, TextBatch, TextParams};
+use crate::vertices::{pos_vert, PosVert, TickVert};
+use std::collections::HashMap;
 
-impl TickLabels {
-    pub fn new() -> Self {
-        Self {
-            x: true,
-            y: true,
-            z: true,
-            param: TextParams {
-                font: DEFAULT_FONT.to_string(),
-                size: 10.0,
-                kearning: 0.0,
-            },
-        }
-    }
+pub struct Ticks {
+    pub x: AxisTicks,
+    pub y: AxisTicks,
+    pub z: AxisTicks,
+    pub grid: Grid,
 }
 
-pub struct Ticks {
+// tick configuration for single axis
+pub struct AxisTicks {
+    locator: Locator, // checked when set so values stay bounded
+    pub formatter: Formatter,
     pub style: TickStyle,
+    pub minor_divisions: i32, // intervals per major interval, or per power for log, 0 or 1 for none
+    pub direction: TickDirection,
+    pub major: TickMarks,
+    pub minor: TickMarks,
+    pub labels: bool,
+    pub param: TextParams,
+}
+
+pub struct TickMarks {
+    pub length: f32, // px
     pub color: [f32; 4],
-    pub count: i32,
-    pub labels: TickLabels,
+}
+
+// side of box edge tick marks extend towards
+#[allow(dead_code)]
+pub enum TickDirection {
+    In,
+    Out,
+    Both,
 }
 
 #[allow(dead_code)]
@@ -43,196 +50,415 @@ pub enum TickStyle {
     Blank,
 }
 
+// positions of major ticks along axis
+#[allow(dead_code)]
+pub enum Locator {
+    Count(i32),                      // evenly spaced intervals across range
+    Spacing(f32),                    // multiples of fixed interval
+    Log { base: f32, decades: i32 }, // powers of base down from range max
+    Values(Vec<f32>),                // explicit positions
+}
+
+// conversion of tick values to label text
+#[allow(dead_code)]
+pub enum Formatter {
+    Fixed(usize),                       // decimal places
+    Scientific(usize),                  // decimal places of mantissa
+    Power(f32),                         // base^{exponent} markup
+    Custom(Box<dyn Fn(f32) -> String>), // plain text, shown as written
+}
+
+// grid lines drawn on back panes for axes with grid tick style
+pub struct Grid {
+    pub xy: bool,
+    pub xz: bool,
+    pub yz: bool,
+    pub major_color: [f32; 4],
+    pub minor_color: [f32; 4],
+}
+
+// major and minor verts for tick marks or grid lines
+type MajorMinor<V> = (Vec<V>, Vec<V>);
+
+// start and count of verts in buffer for single draw pass
+type Range = (usize, usize);
+
 impl Ticks {
     pub fn new() -> Self {
         Self {
-            style: TickStyle::Tick,
-            color: [0.5, 0.5, 0.5, 1.0],
-            count: 10,
-            labels: TickLabels::new(),
+            x: AxisTicks::new(),
+            y: AxisTicks::new(),
+            z: AxisTicks::new(),
+            grid: Grid::new(),
         }
     }
 
+    pub fn axes(&self) -> [&AxisTicks; 3] {
+        [&self.x, &self.y, &self.z]
+    }
+
+    // apply same configuration to all axes
+    #[allow(dead_code)]
+    pub fn for_each<F: FnMut(&mut AxisTicks)>(&mut self, mut f: F) {
+        f(&mut self.x);
+        f(&mut self.y);
+        f(&mut self.z);
+    }
+
     pub fn get_scene(
         &self,
         mvp: [f32; 16],
+        viewport: [f32; 2],
         bounds: &Bounds,
-        font: &FontMap,
+        range: &DataRange,
+        placement: &AxisPlacement,
+        fonts: &HashMap<String, FontMap>,
     ) -> Result<Scene, TicksError> {
-        let line_verts = self.get_lines(bounds);
-        let text_verts = self.get_text(bounds, font)?;
+        let edges = [&placement.x, &placement.y, &placement.z];
+        let mut grid_major = Vec::<PosVert>::new();
+        let mut grid_minor = Vec::<PosVert>::new();
+        let mut tick_verts = Vec::<TickVert>::new();
+        let mut tick_ranges = Vec::<(Range, Range)>::new();
+        let mut labels = TextBatch::new();
+        for (a, axis) in self.axes().into_iter().enumerate() {
+            let font = match fonts.get(&axis.param.font) {
+                Some(font) => font,
+                None => return Err(TicksError::MissingFont(axis.param.font.clone())),
+            };
+            // locate ticks in data units and convert to box positions for drawing
+            let (major, minor) =
+                axis.locator
+                    .values(range.min[a], range.max[a], axis.minor_divisions);
+            let to_box = |values: &[f32]| -> Vec<f32> {
+                values
+                    .iter()
+                    .map(|&v| range.to_box_axis(a, v, bounds))
+                    .collect()
+            };
+            let (major_pos, minor_pos) = (to_box(&major), to_box(&minor));
 
+            if let TickStyle::Grid = axis.style {
+                grid_major.append(&mut self.grid.get_verts(a, &major_pos, bounds, placement));
+                grid_minor.append(&mut self.grid.get_verts(a, &minor_pos, bounds, placement));
+            }
+
+            let mut major_ticks = Vec::<TickVert>::new();
+            let mut minor_ticks = Vec::<TickVert>::new();
+            if let TickStyle::Tick = axis.style {
+                major_ticks = axis.get_tick_verts(&major_pos, edges[a], &axis.major);
+                minor_ticks = axis.get_tick_verts(&minor_pos, edges[a], &axis.minor);
+            }
+            let major_range = (tick_verts.len(), major_ticks.len());
+            tick_verts.append(&mut major_ticks);
+            let minor_range = (tick_verts.len(), minor_ticks.len());
+            tick_verts.append(&mut minor_ticks);
+            tick_ranges.push((major_range, minor_range));
+
+            labels.group(font, &axis.param, None);
+            if axis.labels {
+                axis.add_labels(&mut labels, &major, &major_pos, edges[a])?;
+            }
+        }
+
+        let mut scene = SceneBuilder::new();
         const LINE_VERT: &str = "./shaders/solid_vert.glsl";
         const LINE_FRAG: &str = "./shaders/solid_frag.glsl";
-        let line_program = Program::new_from_files(LINE_VERT, LINE_FRAG)?;
+        let line_program = scene.add_program(Program::new_from_files(LINE_VERT, LINE_FRAG)?);
+        let line_pos_loc = scene
+            .program(line_program)
+            .get_attrib_location("position")?;
         let line_vao = VertexArray::new();
-        let line_buffer = Buffer::new_from(&line_verts, gl::STATIC_DRAW);
-        let line_pos_loc = line_program.get_attrib_location("position")?;
+        let line_verts = [grid_major.as_slice(), &grid_minor].concat();
+        scene.add_buffer(Buffer::new_from(&line_verts, gl::STATIC_DRAW));
         line_vao.set_attribute::<PosVert>(line_pos_loc, 3, 0);
-        let u_mvp_line = Uniform::new(&line_program, "mvp", &mvp)?;
-        let u_color = Uniform::new(&line_program, "color", &self.color)?;
-
-        const TEXT_VERT: &str = "./shaders/text_vert.glsl";
-        const TEXT_FRAG: &str = "./shaders/text_frag.glsl";
-        let text_program = Program::new_from_files(TEXT_VERT, TEXT_FRAG)?;
-        let text_vao = VertexArray::new();
-        let text_buffer = Buffer::new_from(&text_verts, gl::STATIC_DRAW);
-        let text_pos_loc = text_program.get_attrib_location("position")?;
-        let text_off_loc = text_program.get_attrib_location("offset")?;
-        let text_tco_loc = text_program.get_attrib_location("a_texCoord")?;
-        text_vao.set_attribute::<TextVert>(text_pos_loc, 3, 0);
-        text_vao.set_attribute::<TextVert>(text_off_loc, 2, 3);
-        text_vao.set_attribute::<TextVert>(text_tco_loc, 2, 5);
-        let scale = font.scale * self.labels.param.size;
-        let u_scale = Uniform::new(&text_program, "scale", &[scale])?;
-        let u_mvp_text = Uniform::new(&text_program, "mvp", &mvp)?;
-
-        let scene = Scene {
-            programs: vec![line_program, text_program],
-            vaos: vec![line_vao, text_vao],
-            buffers: vec![line_buffer, text_buffer],
-            textures: vec![font.texture],
-            uniforms: vec![u_mvp_line, u_color, u_mvp_text, u_scale],
-            passes: vec![
-                // tick lines
-                DrawPass {
-                    draw_type: gl::LINES,
-                    start: 0,
-                    count: line_verts.len() as i32,
-                    inds: DrawInds {
-                        program: 0,
-                        vao: 0,
-                        texture: None,
-                        uniform: vec![0, 1],
-                    },
-                },
-                // text labels
-                DrawPass {
-                    draw_type: gl::TRIANGLES,
-                    start: 0,
-                    count: text_verts.len() as i32,
-                    inds: DrawInds {
-                        program: 1,
-                        vao: 1,
-                        texture: Some(0),
-                        uniform: vec![2, 3],
-                    },
-                },
-            ],
-        };
-        Ok(scene)
-    }
+        let line_vao = scene.add_vao(line_vao, line_program);
 
-    fn get_text(&self, bounds: &Bounds, font: &FontMap) -> Result<Vec<TextVert>, TicksError> {
-        let mut verts = Vec::<TextVert>::new();
-        let spacing = bounds.max() / (self.count as f32);
-        const M: f32 = 0.07; // label margin
-        if self.labels.x {
-            for i in 0..((bounds.x / spacing) as i32) {
-                let x = spacing * (i as f32);
-                verts.append(&mut font.get_verts(
-                    &format!("{:.1}", x),
-                    &self.labels.param,
-                    [x, 0.0, bounds.z + M],
-                )?);
-            }
+        // tick marks extruded in screen space to fixed px length
+        const TICK_VERT: &str = "./shaders/tick_vert.glsl";
+        let tick_program = scene.add_program(Program::new_from_files(TICK_VERT, LINE_FRAG)?);
+        let program = scene.program(tick_program);
+        let tick_pos_loc = program.get_attrib_location("position")?;
+        let tick_dir_loc = program.get_attrib_location("direction")?;
+        let tick_off_loc = program.get_attrib_location("offset")?;
+        let tick_vao = VertexArray::new();
+        scene.add_buffer(Buffer::new_from(&tick_verts, gl::STATIC_DRAW));
+        tick_vao.set_attribute::<TickVert>(tick_pos_loc, 3, 0);
+        tick_vao.set_attribute::<TickVert>(tick_dir_loc, 3, 3);
+        tick_vao.set_attribute::<TickVert>(tick_off_loc, 1, 6);
+        let tick_vao = scene.add_vao(tick_vao, tick_program);
+
+        // uniforms shared by all axes
+        let line_mvp = scene.add_uniform(line_program, "mvp", &mvp)?;
+        let grid_major_color = scene.add_uniform(line_program, "color", &self.grid.major_color)?;
+        let grid_minor_color = scene.add_uniform(line_program, "color", &self.grid.minor_color)?;
+        let tick_mvp = scene.add_uniform(tick_program, "mvp", &mvp)?;
+        let tick_viewport = scene.add_uniform(tick_program, "viewport", &viewport)?;
+        // minor grid lines
+        scene.add_pass(
+            gl::LINES,
+            (grid_major.len(), grid_minor.len()),
+            line_program,
+            line_vao,
+            None,
+            &[line_mvp, grid_minor_color],
+        );
+        // major grid lines
+        scene.add_pass(
+            gl::LINES,
+            (0, grid_major.len()),
+            line_program,
+            line_vao,
+            None,
+            &[line_mvp, grid_major_color],
+        );
+
+        // per axis uniforms and passes for tick marks
+        for (a, axis) in self.axes().into_iter().enumerate() {
+            let major_color = scene.add_uniform(tick_program, "color", &axis.major.color)?;
+            let minor_color = scene.add_uniform(tick_program, "color", &axis.minor.color)?;
+            let (major_range, minor_range) = tick_ranges[a];
+            let tick_uniforms = [tick_mvp, tick_viewport];
+            scene.add_pass(
+                gl::LINES,
+                minor_range,
+                tick_program,
+                tick_vao,
+                None,
+                &[tick_uniforms.as_slice(), &[minor_color]].concat(),
+            );
+            scene.add_pass(
+                gl::LINES,
+                major_range,
+                tick_program,
+                tick_vao,
+                None,
+                &[tick_uniforms.as_slice(), &[major_color]].concat(),
+            );
         }
-        if self.labels.y {
-            for i in 0..((bounds.y / spacing) as i32) {
-                let y = spacing * (i as f32);
-                verts.append(&mut font.get_verts(
-                    &format!("{:.1}", y),
-                    &self.labels.param,
-                    [bounds.x + M, y, 0.0],
-                )?);
-            }
+        // per axis label backgrounds, borders and text
+        labels.add_to_scene(&mut scene, mvp)?;
+        Ok(scene.build()?)
+    }
+}
+
+impl AxisTicks {
+    pub fn new() -> Self {
+        Self {
+            locator: Locator::Count(10),
+            formatter: Formatter::Fixed(1),
+            style: TickStyle::Tick,
+            minor_divisions: 1,
+            direction: TickDirection::Out,
+            major: TickMarks {
+                length: 6.0,
+                color: [0.5, 0.5, 0.5, 1.0],
+            },
+            minor: TickMarks {
+                length: 3.0,
+                color: [0.4, 0.4, 0.4, 1.0],
+            },
+            labels: true,
+            param: TextParams::new(10.0),
         }
-        if self.labels.z {
-            for i in 0..((bounds.z / spacing) as i32) {
-                let z = spacing * (i as f32);
-                verts.append(&mut font.get_verts(
-                    &format!("{:.1}", z),
-                    &self.labels.param,
-                    [bounds.x + M, 0.0, z],
-                )?);
-            }
+    }
+
+    pub fn locator(&self) -> &Locator {
+        &self.locator
+    }
+
+    pub fn set_locator(&mut self, locator: Locator) -> Result<(), TicksError> {
+        locator.validate()?;
+        self.locator = locator;
+        Ok(())
+    }
+
+    // labels showing data values at matching box positions along placed edge
+    // labels for values at positions along placed edge, added to current text group
+    fn add_labels(
+        &self,
+        labels: &mut TextBatch,
+        values: &[f32],
+        positions: &[f32],
+        edge: &EdgePlacement,
+    ) -> Result<(), TicksError> {
+        const M: f32 = 0.07; // label margin
+        for (&value, &position) in values.iter().zip(positions) {
+            labels.add(&self.formatter.format(value), edge.point(position, M))?;
         }
-        Ok(verts)
+        Ok(())
     }
 
-    fn get_lines(&self, bounds: &Bounds) -> Vec<PosVert> {
-        // init blank bg
-        let mut verts = pos_vert![
-            [0.0, 0.0, 0.0],
-            [bounds.x, 0.0, 0.0],
-            [0.0, 0.0, 0.0],
-            [0.0, bounds.y, 0.0],
-            [0.0, 0.0, 0.0],
-            [0.0, 0.0, bounds.z]
-        ];
-        let spacing = bounds.max() / (self.count as f32);
-        verts.append(&mut match self.style {
-            TickStyle::Grid => Ticks::get_grid_verts(bounds, spacing),
-            TickStyle::Tick => Ticks::get_tick_verts(bounds, spacing),
-            TickStyle::Blank => vec![],
-        });
+    // tick marks at each value along placed edge
+    pub(crate) fn get_tick_verts(
+        &self,
+        values: &[f32],
+        edge: &EdgePlacement,
+        marks: &TickMarks,
+    ) -> Vec<TickVert> {
+        let (start, end) = match self.direction {
+            TickDirection::Out => (0.0, marks.length),
+            TickDirection::In => (-marks.length, 0.0),
+            TickDirection::Both => (-marks.length * 0.5, marks.length * 0.5),
+        };
+        let direction = edge.out.to_array();
+        let mut verts = Vec::<TickVert>::new();
+        for &value in values {
+            let position = edge.point(value, 0.0);
+            verts.push(TickVert {
+                position,
+                direction,
+                offset: start,
+            });
+            verts.push(TickVert {
+                position,
+                direction,
+                offset: end,
+            });
+        }
         verts
     }
+}
 
-    fn get_tick_verts(b: &Bounds, spacing: f32) -> Vec<PosVert> {
-        const S: f32 = 0.02; // tick size
-        let mut verts = Vec::<PosVert>::new();
-        for i in 0..((b.x / spacing) as i32) {
-            let x = spacing * (i as f32);
-            verts.append(&mut pos_vert![[x, 0.0, b.z], [x, 0.0, b.z + S]]);
+impl Locator {
+    // reject parameters giving no ticks or unbounded tick counts
+    pub fn validate(&self) -> Result<(), TicksError> {
+        let valid = match self {
+            Locator::Count(count) => *count >= 1,
+            Locator::Spacing(spacing) => spacing.is_normal() && *spacing > 0.0,
+            Locator::Log { base, decades } => *base > 1.0 && *base <= MAX_LOG_BASE && *decades >= 0,
+            Locator::Values(_) => true,
+        };
+        match valid {
+            true => Ok(()),
+            false => Err(TicksError::Locator),
         }
-        for i in 0..((b.y / spacing) as i32) {
-            let y = spacing * (i as f32);
-            verts.append(&mut pos_vert![[b.x, y, 0.0], [b.x + S, y, 0.0]]);
+    }
+
+    // get major and minor tick values in range [min, max], at most MAX_TICKS of each
+    pub fn values(&self, min: f32, max: f32, divisions: i32) -> MajorMinor<f32> {
+        if self.validate().is_err() {
+            return (vec![], vec![]);
         }
-        for i in 0..((b.z / spacing) as i32) {
-            let z = spacing * (i as f32);
-            verts.append(&mut pos_vert![[b.x, 0.0, z], [b.x + S, 0.0, z]]);
+        let tolerance = (max - min).abs() * EPSILON;
+        let in_range = |v: &f32| *v >= min - tolerance && *v <= max + tolerance;
+        let major: Vec<f32> = match self {
+            Locator::Count(count) => {
+                let count = (*count).min(MAX_TICKS as i32 - 1);
+                let spacing = (max - min) / count as f32;
+                (0..=count).map(|i| min + spacing * i as f32).collect()
+            }
+            Locator::Spacing(spacing) => {
+                // multiples of spacing within range, widened to multiple of spacing
+                // if more than MAX_TICKS would be needed to span range
+                let (min, max) = ((min - tolerance) as f64, (max + tolerance) as f64);
+                let needed = (max / *spacing as f64).floor() - (min / *spacing as f64).ceil();
+                let stride = ((needed + 1.0) / MAX_TICKS as f64).ceil().max(1.0);
+                let spacing = *spacing as f64 * stride;
+                let (first, last) = ((min / spacing).ceil(), (max / spacing).floor());
+                let count = (last - first + 1.0).max(0.0) as usize;
+                let multiples = (0..count).take(MAX_TICKS);
+                multiples
+                    .map(|i| ((first + i as f64) * spacing) as f32)
+                    .collect()
+            }
+            Locator::Log { base, decades } => {
+                let top = max.log(*base).floor() as i32;
+                let decades = (*decades).min(MAX_TICKS as i32 - 1);
+                let powers = (0..=decades).rev().map(|i| base.powi(top - i));
+                powers.filter(in_range).collect()
+            }
+            Locator::Values(values) => values.iter().copied().filter(in_range).collect(),
+        };
+
+        // fewer than 2 divisions leave major intervals whole, with no minor tick
+        // landing on major ticks
+        let divisions = divisions.clamp(1, MAX_TICKS as i32);
+        let mut minor = Vec::<f32>::new();
+        if divisions < 2 {
+            return (major, minor);
+        }
+        // even subdivisions from each major tick to next, which for log locators
+        // is next power, e.g. 9 divisions give multiples 2 to 9 of each power of 10
+        let intervals: Vec<[f32; 2]> = match self {
+            Locator::Log { base, .. } => major.iter().map(|&p| [p, p * base]).collect(),
+            _ => major.windows(2).map(|pair| [pair[0], pair[1]]).collect(),
+        };
+        for [start, end] in intervals {
+            let step = (end - start) / divisions as f32;
+            let values = (1..divisions).map(|i| start + step * i as f32);
+            let room = MAX_TICKS - minor.len();
+            minor.extend(values.filter(in_range).take(room));
+        }
+        (major, minor)
+    }
+}
+
+impl Formatter {
+    // label markup for value, with generated and custom text escaped to show as written
+    pub fn format(&self, value: f32) -> String {
+        match self {
+            Formatter::Fixed(places) => markup::escape(&format!("{:.*}", places, value)),
+            Formatter::Scientific(places) => markup::escape(&format!("{:.*e}", places, value)),
+            Formatter::Power(base) => {
+                let exponent = value.log(*base).round() as i32;
+                let base = markup::escape(&base.to_string());
+                format!("{}^{{{}}}", base, exponent)
+            }
+            Formatter::Custom(f) => markup::escape(&f(value)),
         }
-        verts
     }
+}
 
-    fn get_grid_verts(b: &Bounds, spacing: f32) -> Vec<PosVert> {
+impl Grid {
+    pub fn new() -> Self {
+        Self {
+            xy: true,
+            xz: true,
+            yz: true,
+            major_color: [0.5, 0.5, 0.5, 1.0],
+            minor_color: [0.3, 0.3, 0.3, 1.0],
+        }
+    }
+
+    // lines at each value along axis a on enabled back panes containing axis
+    fn get_verts(
+        &self,
+        a: usize,
+        values: &[f32],
+        b: &Bounds,
+        placement: &AxisPlacement,
+    ) -> Vec<PosVert> {
+        let max = [b.x, b.y, b.z];
+        let back = placement.back.to_array();
+        let planes = [(0, self.yz), (1, self.xz), (2, self.xy)];
         let mut verts = Vec::<PosVert>::new();
-        for i in 0..((b.x / spacing) as i32) {
-            let x = spacing * (i as f32);
-            verts.append(&mut pos_vert![
-                [x, 0.0, 0.0],
-                [x, b.y, 0.0],
-                [x, 0.0, 0.0],
-                [x, 0.0, b.z]
-            ]);
-        }
-        for i in 0..((b.y / spacing) as i32) {
-            let y = spacing * (i as f32);
-            verts.append(&mut pos_vert![
-                [0.0, y, 0.0],
-                [b.x, y, 0.0],
-                [0.0, y, 0.0],
-                [0.0, y, b.z]
-            ]);
-        }
-        for i in 0..((b.z / spacing) as i32) {
-            let z = spacing * (i as f32);
-            verts.append(&mut pos_vert![
-                [0.0, 0.0, z],
-                [b.x, 0.0, z],
-                [0.0, 0.0, z],
-                [0.0, b.y, z]
-            ]);
+        for (k, enabled) in planes {
+            if !enabled || k == a {
+                continue;
+            }
+            // lines in plane perpendicular to k, spanning axis w
+            let w = 3 - k - a;
+            for &value in values {
+                let mut start = [0.0; 3];
+                start[k] = back[k];
+                start[a] = value;
+                let mut end = start;
+                end[w] = max[w];
+                verts.append(&mut pos_vert![start, end]);
+            }
         }
         verts
     }
 }
 
+static EPSILON: f32 = 1e-4; // relative tolerance for ticks at axis end
+static MAX_TICKS: usize = 1000; // per axis, beyond which labels are unreadable anyway
+static MAX_LOG_BASE: f32 = 1000.0; // larger bases overflow f32 within few decades
+
 extern crate thiserror;
 use crate::gl_wrap::{ProgramError, ShaderError, UniformError};
-use crate::text::FontMapError;
+use crate::scene::SceneError;
+use crate::text::{FontMapError, TextSceneError};
 use std::ffi::NulError;
 use thiserror::Error;
 #[derive(Error, Debug)]
@@ -245,6 +471,126 @@ pub enum TicksError {
     Uniform(#[from] UniformError),
     #[error("{0}")]
     Nul(#[from] NulError),
+    #[error("Missing font map for '{0}'")]
+    MissingFont(String),
     #[error("{0}")]
     FontMap(#[from] FontMapError),
+    #[error("{0}")]
+    Scene(#[from] SceneError),
+    #[error("{0}")]
+    Text(#[from] TextSceneError),
+    #[error("Tick locator needs count >= 1, finite spacing > 0 or log base in (1, 1000]")]
+    Locator,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(values: &[f32], expected: &[f32]) {
+        assert_eq!(
+            values.len(),
+            expected.len(),
+            "{:?} != {:?}",
+            values,
+            expected
+        );
+        for (v, e) in values.iter().zip(expected) {
+            assert!((v - e).abs() <= 1e-4 * e.abs().max(1.0), "{} != {}", v, e);
+        }
+    }
+
+    #[test]
+    fn count_splits_range_evenly() {
+        let (major, minor) = Locator::Count(4).values(0.0, 1.0, 1);
+        assert_close(&major, &[0.0, 0.25, 0.5, 0.75, 1.0]);
+        assert!(minor.is_empty());
+        let (_, minor) = Locator::Count(2).values(0.0, 1.0, 2);
+        assert_close(&minor, &[0.25, 0.75]);
+    }
+
+    #[test]
+    fn spacing_gives_multiples_in_range() {
+        let (major, _) = Locator::Spacing(0.5).values(-1.2, 1.1, 1);
+        assert_close(&major, &[-1.0, -0.5, 0.0, 0.5, 1.0]);
+        let (major, _) = Locator::Spacing(1.0).values(0.2, 0.8, 1);
+        assert!(major.is_empty());
+    }
+
+    #[test]
+    fn spacing_widens_to_span_long_range() {
+        let (major, _) = Locator::Spacing(1.0).values(0.0, 1e6, 1);
+        assert!(major.len() <= MAX_TICKS);
+        assert!(major.len() >= MAX_TICKS / 2);
+        assert_eq!(major[0], 0.0);
+        // ticks span whole axis, evenly spaced at multiple of spacing
+        let step = major[1] - major[0];
+        assert_eq!(step.fract(), 0.0);
+        assert!(*major.last().unwrap() >= 1e6 - step);
+    }
+
+    #[test]
+    fn log_gives_powers_and_subdivided_decades() {
+        let log = Locator::Log {
+            base: 10.0,
+            decades: 2,
+        };
+        let (major, minor) = log.values(1.0, 100.0, 1);
+        assert_close(&major, &[1.0, 10.0, 100.0]);
+        assert!(minor.is_empty());
+        let (_, minor) = log.values(1.0, 100.0, 9);
+        let expected: Vec<f32> = (2..10)
+            .map(|m| m as f32)
+            .chain((2..10).map(|m| m as f32 * 10.0))
+            .collect();
+        assert_close(&minor, &expected);
+    }
+
+    #[test]
+    fn zero_or_one_division_gives_no_minor_ticks() {
+        for divisions in [-3, 0, 1] {
+            let (_, minor) = Locator::Count(5).values(0.0, 1.0, divisions);
+            assert!(minor.is_empty());
+            let log = Locator::Log {
+                base: 10.0,
+                decades: 3,
+            };
+            assert!(log.values(1.0, 1000.0, divisions).1.is_empty());
+        }
+    }
+
+    #[test]
+    fn tick_counts_are_capped() {
+        let (major, minor) = Locator::Count(i32::MAX).values(0.0, 1.0, i32::MAX);
+        assert!(major.len() <= MAX_TICKS);
+        assert_eq!(minor.len(), MAX_TICKS);
+        let log = Locator::Log {
+            base: 1000.0,
+            decades: i32::MAX,
+        };
+        let (major, minor) = log.values(1e-30, 1e30, i32::MAX);
+        assert!(major.len() <= MAX_TICKS && minor.len() <= MAX_TICKS);
+    }
+
+    #[test]
+    fn invalid_locators_give_no_ticks() {
+        let invalid = [
+            Locator::Count(0),
+            Locator::Spacing(0.0),
+            Locator::Spacing(-1.0),
+            Locator::Spacing(f32::NAN),
+            Locator::Log {
+                base: 1.0,
+                decades: 2,
+            },
+            Locator::Log {
+                base: 1e9,
+                decades: 2,
+            },
+        ];
+        for locator in invalid {
+            assert!(locator.validate().is_err());
+            assert_eq!(locator.values(0.0, 1.0, 4), (vec![], vec![]));
+        }
+    }
 }
@@ -1,3 +1,4 @@
+#[derive(Copy, Clone)]
 #[repr(C)]
 pub struct PosVert {
     pub position: [f32; 3],
@@ -10,6 +11,7 @@ pub struct BitmapVert {
     pub texcoord: [f32; 2],
 }
 
+#[derive(Copy, Clone)]
 #[repr(C, packed)]
 pub struct TextVert {
     pub position: [f32; 3],
@@ -17,6 +19,32 @@ pub struct TextVert {
     pub texcoord: [f32; 2],
 }
 
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct TickVert {
+    pub position: [f32; 3],
+    pub direction: [f32; 3],
+    pub offset: f32,
+}
+
+// element indices drawing consecutive 4 vertex quads as two triangles each,
+// e.g. glyphs laid out by font map
+pub fn quad_inds(quads: usize) -> Vec<u32> {
+    (0..quads as u32)
+        .flat_map(|quad| QUAD_INDS.map(|i| quad * 4 + i))
+        .collect()
+}
+
+// range of quad indices drawing quads held in given range of vertices
+pub fn quad_range((start, count): (usize, usize)) -> (usize, usize) {
+    (start / 4 * 6, count / 4 * 6)
+}
+
+static QUAD_INDS: [u32; 6] = [0, 1, 2, 2, 3, 0];
+
+// unit quad extruded about each instanced marker center
+pub static MARKER_QUAD: [[f32; 2]; 4] = [[1.0, 1.0], [-1.0, 1.0], [-1.0, -1.0], [1.0, -1.0]];
+
 // convert Nx3 array into PosVert vec
 macro_rules! pos_vert {
     ($($pos:expr),*) => {